@@ -1,7 +1,5 @@
 use crate::encoding::read_exact_at;
-use crate::model::{
-    Checksum8, Checksum16, DEFAULT_LENGTH, Endian, MemoryLayoutType, NvramMap, Platform,
-};
+use crate::model::{Checksum8, Checksum16, DEFAULT_LENGTH, Endian, NvramMap, Platform};
 use std::io;
 use std::io::{Read, Seek, SeekFrom, Write};
 
@@ -21,6 +19,7 @@ pub struct ChecksumMismatch<T> {
 pub(crate) fn verify_checksum8<T: Read + Seek>(
     nvram_file: &mut T,
     checksum8: &Checksum8,
+    platform: &Platform,
 ) -> io::Result<Option<ChecksumMismatch<u8>>> {
     let start: u64 = (&checksum8.start).into();
 
@@ -33,12 +32,17 @@ pub(crate) fn verify_checksum8<T: Read + Seek>(
             Some(e) => u64::from(e),
             None => start + checksum8.length.unwrap_or(DEFAULT_LENGTH as u64) - 1,
         };
+        let (start, data_end) = (
+            checksum8_offset(platform, start)?,
+            checksum8_offset(platform, data_end)?,
+        );
         let mut buff = vec![0; (data_end - start + 1) as usize];
         read_exact_at(nvram_file, start, &mut buff)?;
         let calc_sum: u8 = 0xFFu8 - buff.iter().fold(0u8, |acc, &x| acc.wrapping_add(x));
 
         let mut checksum_byte = [0u8; 1];
-        read_exact_at(nvram_file, checksum_address, &mut checksum_byte)?;
+        let checksum_offset = checksum8_offset(platform, checksum_address)?;
+        read_exact_at(nvram_file, checksum_offset, &mut checksum_byte)?;
         let stored_sum = checksum_byte[0];
 
         return if calc_sum != stored_sum {
@@ -60,6 +64,10 @@ pub(crate) fn verify_checksum8<T: Read + Seek>(
         }
     };
 
+    let (start, end) = (
+        checksum8_offset(platform, start)?,
+        checksum8_offset(platform, end)?,
+    );
     let group_ranges: Vec<[u64; 2]> = groupings_to_ranges(start, end, &checksum8.groupings)?;
 
     let checksum_failures_result: io::Result<Vec<ChecksumMismatch<u8>>> = group_ranges
@@ -76,6 +84,69 @@ pub(crate) fn verify_checksum8<T: Read + Seek>(
     }
 }
 
+/// Write the checksum byte of each range, the counterpart of [`verify_checksum8`]
+fn update_checksum8<T: Read + Seek + Write>(
+    nvram_file: &mut T,
+    checksum8: &Checksum8,
+    platform: &Platform,
+) -> io::Result<()> {
+    let start: u64 = (&checksum8.start).into();
+
+    if let Some(checksum_address) = checksum8.checksum {
+        let data_end: u64 = match &checksum8.end {
+            Some(e) => u64::from(e),
+            None => start + checksum8.length.unwrap_or(DEFAULT_LENGTH as u64) - 1,
+        };
+        let (start, data_end) = (
+            checksum8_offset(platform, start)?,
+            checksum8_offset(platform, data_end)?,
+        );
+        let mut buff = vec![0; (data_end - start + 1) as usize];
+        read_exact_at(nvram_file, start, &mut buff)?;
+        let calc_sum: u8 = 0xFFu8 - buff.iter().fold(0u8, |acc, &x| acc.wrapping_add(x));
+        let checksum_offset = checksum8_offset(platform, checksum_address)?;
+        nvram_file.seek(SeekFrom::Start(checksum_offset))?;
+        return nvram_file.write_all(&[calc_sum]);
+    }
+
+    let end: u64 = match &checksum8.end {
+        Some(e) => u64::from(e),
+        None => {
+            let length: u64 = checksum8.length.unwrap_or(DEFAULT_LENGTH as u64);
+            start + length
+        }
+    };
+
+    let (start, end) = (
+        checksum8_offset(platform, start)?,
+        checksum8_offset(platform, end)?,
+    );
+    for [range_start, range_end] in groupings_to_ranges(start, end, &checksum8.groupings)? {
+        let mut buff = vec![0; (range_end - range_start) as usize];
+        read_exact_at(nvram_file, range_start, &mut buff)?;
+        let calc_sum: u8 = 0xFFu8 - buff.iter().fold(0u8, |acc, &x| acc.wrapping_add(x));
+        nvram_file.seek(SeekFrom::Start(range_end))?;
+        nvram_file.write_all(&[calc_sum])?;
+    }
+    Ok(())
+}
+
+pub(crate) fn update_all_checksum8<T: Read + Seek + Write>(
+    mut nvram_file: &mut T,
+    map: &NvramMap,
+    platform: &Platform,
+) -> io::Result<()> {
+    map.checksum8
+        .iter()
+        .flatten()
+        .try_for_each(|cs| update_checksum8(&mut nvram_file, cs, platform))
+}
+
+/// The offset in the .nv file of a checksum8 address
+fn checksum8_offset(platform: &Platform, address: u64) -> io::Result<u64> {
+    file_offset(platform, "Checksum8", address)
+}
+
 /// Convert the groupings into a list of ranges
 /// The end is inclusive
 /// THe returned groupings are also inclusive
@@ -137,16 +208,14 @@ fn verify_checksum8_range<T: Read + Seek>(
 pub(crate) fn verify_checksum16<T: Read + Seek>(
     nvram_file: &mut T,
     checksum16: &Checksum16,
-    endian: Endian,
-    offset: u64,
+    platform: &Platform,
 ) -> io::Result<Option<ChecksumMismatch<u16>>> {
-    let start = u64::from(&checksum16.start) - offset;
-    let end = end(checksum16, offset)?;
+    let (start, end) = range(checksum16, platform)?;
     let length = (1 + end - start) as usize;
     let mut buff = vec![0; length];
     read_exact_at(nvram_file, start, &mut buff)?;
 
-    let stored_sum = match endian {
+    let stored_sum = match platform.endian {
         Endian::Big => (buff.pop().unwrap() as u16) + ((buff.pop().unwrap() as u16) << 8),
         Endian::Little => ((buff.pop().unwrap() as u16) << 8) + buff.pop().unwrap() as u16,
     };
@@ -163,19 +232,34 @@ pub(crate) fn verify_checksum16<T: Read + Seek>(
     Ok(None)
 }
 
-fn end(checksum16: &Checksum16, offset: u64) -> io::Result<u64> {
+/// The inclusive range of the checksum16 region as offsets in the .nv file
+fn range(checksum16: &Checksum16, platform: &Platform) -> io::Result<(u64, u64)> {
     let start: u64 = (&checksum16.start).into();
     let end: u64 = if let Some(end) = &checksum16.end {
-        u64::from(end) - offset
+        u64::from(end)
     } else if let Some(length) = &checksum16.length {
-        start + length - 1 - offset
+        start + length - 1
     } else {
         return Err(io::Error::new(
             io::ErrorKind::InvalidData,
             "Checksum16 must have either end or length",
         ));
     };
-    Ok(end)
+    Ok((
+        file_offset(platform, "Checksum16", start)?,
+        file_offset(platform, "Checksum16", end)?,
+    ))
+}
+
+/// The offset in the .nv file of a checksum address, `checksum` names the kind of checksum
+/// in the error
+fn file_offset(platform: &Platform, checksum: &str, address: u64) -> io::Result<u64> {
+    platform.file_offset(address).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{checksum} address {address:#06x} is outside the NVRAM"),
+        )
+    })
 }
 
 pub(crate) fn verify_all_checksum16<T: Read + Seek>(
@@ -183,12 +267,10 @@ pub(crate) fn verify_all_checksum16<T: Read + Seek>(
     map: &NvramMap,
     platform: &Platform,
 ) -> io::Result<Vec<ChecksumMismatch<u16>>> {
-    let endian = platform.endian;
-    let offset = platform.offset(MemoryLayoutType::NVRam);
     map.checksum16
         .iter()
         .flatten()
-        .map(|cs| verify_checksum16(&mut nvram_file, cs, endian, offset))
+        .map(|cs| verify_checksum16(&mut nvram_file, cs, platform))
         .filter_map(|r| r.transpose())
         .collect()
 }
@@ -196,11 +278,9 @@ pub(crate) fn verify_all_checksum16<T: Read + Seek>(
 fn update_checksum16<T: Read + Seek + Write>(
     nvram_file: &mut T,
     checksum16: &Checksum16,
-    endian: Endian,
-    offset: u64,
+    platform: &Platform,
 ) -> io::Result<()> {
-    let start: u64 = u64::from(&checksum16.start) - offset;
-    let end: u64 = end(checksum16, offset)?;
+    let (start, end) = range(checksum16, platform)?;
     let length = (1 + end - start) as usize;
 
     let mut buff = vec![0; length - 2];
@@ -210,7 +290,7 @@ fn update_checksum16<T: Read + Seek + Write>(
     let calc_sum: u16 = 0xFFFFu16 - buff.iter().fold(0u16, |acc, &x| acc.wrapping_add(x as u16));
 
     // push the calculated sum to the end of the buffer
-    match endian {
+    match platform.endian {
         Endian::Big => {
             buff.push((calc_sum >> 8) as u8);
             buff.push((calc_sum & 0xFF) as u8);
@@ -232,12 +312,10 @@ pub(crate) fn update_all_checksum16<T: Read + Seek + Write>(
     map: &NvramMap,
     platform: &Platform,
 ) -> io::Result<()> {
-    let endian = platform.endian;
-    let offset = platform.offset(MemoryLayoutType::NVRam);
     map.checksum16
         .iter()
         .flatten()
-        .try_for_each(|cs| update_checksum16(&mut nvram_file, cs, endian, offset))
+        .try_for_each(|cs| update_checksum16(&mut nvram_file, cs, platform))
 }
 
 #[cfg(test)]
@@ -246,11 +324,19 @@ mod test {
     use crate::Nvram;
     use crate::checksum::ChecksumMismatch;
     use crate::model::{Checksum8, HexOrInteger};
+    use crate::test_util;
     use pretty_assertions::assert_eq;
     use std::fs::OpenOptions;
     use std::io;
     use std::path::Path;
 
+    /// A platform with 16 bytes of NVRAM at the address
+    fn nvram_at(address: u64) -> Platform {
+        test_util::platform(serde_json::json!([
+            {"label": "NVRAM", "address": address, "size": 16, "type": "nvram"}
+        ]))
+    }
+
     #[test]
     fn test_verify_checksum8_range() -> io::Result<()> {
         let mut cursor = io::Cursor::new([0xAA, 0x11, 0x44]);
@@ -283,7 +369,7 @@ mod test {
             groupings: None,
             _notes: None,
         };
-        let result = verify_checksum8(&mut cursor, &checksum8);
+        let result = verify_checksum8(&mut cursor, &checksum8, &nvram_at(0x0000));
         assert_eq!(None, result?);
         Ok(())
     }
@@ -303,7 +389,7 @@ mod test {
             groupings: None,
             _notes: None,
         };
-        let result = verify_checksum8(&mut cursor, &checksum8);
+        let result = verify_checksum8(&mut cursor, &checksum8, &nvram_at(0x0000));
         assert_eq!(None, result?);
         Ok(())
     }
@@ -325,7 +411,10 @@ mod test {
             groupings: None,
             _notes: None,
         };
-        assert_eq!(None, verify_checksum8(&mut cursor, &checksum8)?);
+        assert_eq!(
+            None,
+            verify_checksum8(&mut cursor, &checksum8, &nvram_at(0x0000))?
+        );
         Ok(())
     }
 
@@ -351,7 +440,7 @@ mod test {
                 expected: 0xFF,
                 calculated: 0x00,
             }),
-            verify_checksum8(&mut cursor, &checksum8)?
+            verify_checksum8(&mut cursor, &checksum8, &nvram_at(0x0000))?
         );
         Ok(())
     }
@@ -372,11 +461,95 @@ mod test {
             groupings: Some(3),
             _notes: None,
         };
-        let result = verify_checksum8(&mut cursor, &checksum8);
+        let result = verify_checksum8(&mut cursor, &checksum8, &nvram_at(0x0000));
         assert_eq!(None, result?);
         Ok(())
     }
 
+    #[test]
+    fn test_update_checksum8_grouped() -> io::Result<()> {
+        #[rustfmt::skip]
+        let mut cursor = io::Cursor::new([
+            0xAA, 0x11, 0x00,
+            0xFF, 0x00, 0x12
+        ]);
+        let checksum8 = Checksum8 {
+            label: "test".to_string(),
+            start: HexOrInteger::Integer(0),
+            end: Some(HexOrInteger::Integer(5)),
+            length: None,
+            checksum: None,
+            groupings: Some(3),
+            _notes: None,
+        };
+        update_checksum8(&mut cursor, &checksum8, &nvram_at(0x0000))?;
+        assert_eq!(cursor.get_ref(), &[0xAA, 0x11, 0x44, 0xFF, 0x00, 0x00]);
+        assert_eq!(
+            None,
+            verify_checksum8(&mut cursor, &checksum8, &nvram_at(0x0000))?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_checksum8_non_adjacent_checksum() -> io::Result<()> {
+        #[rustfmt::skip]
+        let mut cursor = io::Cursor::new([
+            0x10, 0x00, 0x00, 0x00, 0xFF
+        ]);
+        let checksum8 = Checksum8 {
+            label: "Credits".to_string(),
+            start: HexOrInteger::Integer(0),
+            end: None,
+            length: None,
+            checksum: Some(4),
+            groupings: None,
+            _notes: None,
+        };
+        update_checksum8(&mut cursor, &checksum8, &nvram_at(0x0000))?;
+        assert_eq!(cursor.get_ref(), &[0x10, 0x00, 0x00, 0x00, 0xEF]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_checksum8_cpu_addresses() -> io::Result<()> {
+        let platform = nvram_at(0x2000);
+        let mut cursor = io::Cursor::new([0xAA, 0x11, 0x00, 0x10, 0x00]);
+        let grouped = Checksum8 {
+            label: "test".to_string(),
+            start: HexOrInteger::Integer(0x2000),
+            end: Some(HexOrInteger::Integer(0x2002)),
+            length: None,
+            checksum: None,
+            groupings: Some(3),
+            _notes: None,
+        };
+        let non_adjacent = Checksum8 {
+            label: "Credits".to_string(),
+            start: HexOrInteger::Integer(0x2003),
+            end: None,
+            length: None,
+            checksum: Some(0x2004),
+            groupings: None,
+            _notes: None,
+        };
+        update_checksum8(&mut cursor, &grouped, &platform)?;
+        update_checksum8(&mut cursor, &non_adjacent, &platform)?;
+        assert_eq!(cursor.get_ref(), &[0xAA, 0x11, 0x44, 0x10, 0xEF]);
+        assert_eq!(None, verify_checksum8(&mut cursor, &grouped, &platform)?);
+        assert_eq!(
+            None,
+            verify_checksum8(&mut cursor, &non_adjacent, &platform)?
+        );
+
+        let result = verify_checksum8(&mut cursor, &grouped, &nvram_at(0x4000));
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Checksum8 address 0x2000 is outside the NVRAM"
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_verify_checksum8_range_mismatch() -> io::Result<()> {
         let mut cursor = io::Cursor::new([0xAA, 0x11, 0xFF]);
@@ -11,7 +11,7 @@ use std::io::{Read, Seek, SeekFrom, Write};
 /// PinMAME has a maximum of 10 banks with 8 switches each
 /// Only 6 bytes are written to the nvram file
 /// https://github.com/vpinball/pinmame/blob/f14bbc89c48d0ecb0d44d4be7a694730cfbf24e1/src/wpc/core.c#L2303-L2309
-const DIP_SWITCH_BYTES: usize = 6;
+pub(crate) const DIP_SWITCH_BYTES: usize = 6;
 
 /// Maximum number of dip switches that we can handle
 pub const MAX_SWITCH_COUNT: usize = DIP_SWITCH_BYTES * 8;
@@ -1,8 +1,9 @@
-use crate::model::{DEFAULT_SCALE, Endian, Nibble, Null};
-use serde_json::Number;
+use crate::model::{Endian, Nibble, Null};
+use crate::value::{NvValue, Timestamp};
 use std::io;
 use std::io::{Read, Seek, SeekFrom, Write};
 
+#[derive(Clone)]
 pub(crate) enum Location {
     Continuous { start: u64, length: usize },
     Scattered { offsets: Vec<u64> },
@@ -14,7 +15,10 @@ pub(crate) enum Location {
 /// A `Scattered` location reads a single byte from each offset, in order. This
 /// is used by platforms like Capcom where the 8-bit NVRAM is mapped on a 16-bit
 /// bus, so the bytes that make up a value live at non-consecutive file offsets.
-fn read_location<A: Read + Seek>(stream: &mut A, location: &Location) -> io::Result<Vec<u8>> {
+pub(crate) fn read_location<A: Read + Seek>(
+    stream: &mut A,
+    location: &Location,
+) -> io::Result<Vec<u8>> {
     match location {
         Location::Continuous { start, length } => {
             let mut buff = vec![0; *length];
@@ -33,6 +37,49 @@ fn read_location<A: Read + Seek>(stream: &mut A, location: &Location) -> io::Res
     }
 }
 
+/// Write raw bytes to a location, the counterpart of [`read_location`].
+pub(crate) fn write_location<A: Write + Seek>(
+    stream: &mut A,
+    location: &Location,
+    buff: &[u8],
+) -> io::Result<()> {
+    match location {
+        Location::Continuous { start, length } => {
+            if buff.len() != *length {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Expected {} bytes but got {}", length, buff.len()),
+                ));
+            }
+            stream.seek(SeekFrom::Start(*start))?;
+            stream.write_all(buff)
+        }
+        Location::Scattered { offsets } => {
+            if buff.len() != offsets.len() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Expected {} bytes but got {}", offsets.len(), buff.len()),
+                ));
+            }
+            for (offset, byte) in offsets.iter().zip(buff) {
+                stream.seek(SeekFrom::Start(*offset))?;
+                stream.write_all(&[*byte])?;
+            }
+            Ok(())
+        }
+    }
+}
+
+impl Location {
+    /// The number of bytes at this location
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            Location::Continuous { length, .. } => *length,
+            Location::Scattered { offsets } => offsets.len(),
+        }
+    }
+}
+
 pub(crate) fn de_nibble(length: usize, buff: &[u8], nibble: Nibble) -> io::Result<Vec<u8>> {
     if nibble == Nibble::Both {
         return Ok(buff.to_vec());
@@ -108,102 +155,253 @@ pub(crate) fn do_nibble(length: usize, buff: &[u8], nibble: Nibble) -> io::Resul
     Ok(result)
 }
 
+/// How the bytes of a `ch` descriptor map to characters
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Charset {
+    /// Printable ASCII, with `period_flag` the high bit is the [`PERIOD_FLAG`]
+    Ascii { period_flag: bool },
+    /// Each byte is the index of a character in the `_char_map` of the map
+    Map(String),
+}
+
+impl Charset {
+    pub(crate) fn new(char_map: &Option<String>, period_flag: bool) -> Self {
+        match char_map {
+            Some(char_map) => Charset::Map(char_map.clone()),
+            None => Charset::Ascii { period_flag },
+        }
+    }
+}
+
+/// Read a string, the options of the descriptor are applied in this order:
+/// * `nibble` - combine the nibbles into bytes
+/// * `mask` - clear bits of each byte
+/// * `null` - skip zero bytes or end the string at the first one
+/// * `charset` - turn each byte into a character
+///
+/// If any byte is not a character the result is a [`NvValue::UndecodedText`].
 pub(crate) fn read_ch<A: Read + Seek>(
     stream: &mut A,
     location: Location,
     mask: Option<u64>,
-    char_map: &Option<String>,
+    charset: &Charset,
     nibble: Nibble,
     null: Option<Null>,
-) -> io::Result<String> {
-    let mut buff = read_location(stream, &location)?;
-    let length = buff.len();
+) -> io::Result<NvValue> {
+    let buff = read_location(stream, &location)?;
+    let buff = de_nibble(buff.len(), &buff, nibble)?;
+    Ok(decode_ch(&buff, mask, charset, null))
+}
 
-    if nibble != Nibble::Both {
-        let result = de_nibble(length, &buff, nibble)?;
-        // filter out zero bytes
-        let result = result.into_iter().filter(|&b| b != 0).collect::<Vec<u8>>();
-        return String::from_utf8(result.to_vec())
-            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e));
+fn decode_ch(buff: &[u8], mask: Option<u64>, charset: &Charset, null: Option<Null>) -> NvValue {
+    let mut text = String::new();
+    let mut raw = Vec::new();
+    let mut decoded = true;
+    for b in buff {
+        let b = mask.map_or(*b, |mask| b & mask as u8);
+        if b == 0 {
+            match null {
+                Some(Null::Ignore) => continue,
+                Some(Null::Truncate | Null::Terminate) => break,
+                None => {}
+            }
+        }
+        raw.push(b);
+        let pushed = match charset {
+            Charset::Map(char_map) => match char_map.chars().nth(b as usize) {
+                Some(character) => {
+                    text.push(character);
+                    true
+                }
+                None => false,
+            },
+            Charset::Ascii { period_flag } => push_display_char(&mut text, b, *period_flag),
+        };
+        if !pushed {
+            text.push(UNDECODED);
+            decoded = false;
+        }
+    }
+    if decoded {
+        NvValue::Text(text)
+    } else {
+        NvValue::UndecodedText { text, raw }
     }
+}
 
-    // apply mask if set
-    if let Some(mask) = mask {
-        for b in buff.iter_mut() {
-            *b &= mask as u8;
-        }
+/// On alphanumeric displays the high bit lights the period/comma segment of a character,
+/// e.g. High Speed's "Custom Msg Line 3" has 0xC4 for "D."
+const PERIOD_FLAG: u8 = 0x80;
+
+/// Shown for bytes that are not a character, e.g. 0xFF in unused initials
+const UNDECODED: char = char::REPLACEMENT_CHARACTER;
+
+/// Decode a printable character, with the `period_flag` a character with the
+/// [`PERIOD_FLAG`] gets a trailing `.`. Returns `false` for other bytes.
+fn push_display_char(text: &mut String, b: u8, period_flag: bool) -> bool {
+    let character = if period_flag { b & !PERIOD_FLAG } else { b };
+    if !is_printable(character) {
+        return false;
     }
-    // if char_map is set, convert the buffer to a string
-    if let Some(char_map) = char_map {
-        let mut result = String::new();
-        for b in buff.iter() {
-            result.push(char_map.chars().nth(*b as usize).unwrap_or('?'));
-        }
-        return Ok(result);
+    text.push(character as char);
+    if character != b {
+        text.push('.');
     }
+    true
+}
 
-    let null_terminated = null == Some(Null::Terminate) || null == Some(Null::Truncate);
+fn is_printable(b: u8) -> bool {
+    (0x20..0x7F).contains(&b)
+}
 
-    //String::from_utf8(buff.to_vec()).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
-    // TODO we probably need a better solution for this
-    // See High Speed where "Custom Msg Line 3" contains invalid characters by default
-    // A letter with a period is encoded as 0x80 (period) + letter
-    // can we replace any non-utf8 characters with \u00C4 in the string?
-    let mut result = String::new();
-    for b in buff.iter() {
-        if null_terminated && *b == 0 {
-            break;
-        }
-        if *b > 0x80 {
-            // use \u escape for non-ASCII characters;
-            // this is not perfect as it will cause double encoding for json
-            // TODO clean this up
-            result.push_str(&format!("\\u{:04X}", *b));
-        } else {
-            result.push(*b as char);
+/// Encode text as printable characters, the reverse of [`push_display_char`].
+///
+/// With the `period_flag` a `.` or `,` after a character is folded into the [`PERIOD_FLAG`]
+/// of that character, both light the same segment and read back as `.`.
+fn encode_display_text(value: &str, period_flag: bool) -> io::Result<Vec<u8>> {
+    let mut buff: Vec<u8> = Vec::with_capacity(value.len());
+    for character in value.chars() {
+        match buff.last_mut() {
+            Some(last)
+                if period_flag
+                    && (character == '.' || character == ',')
+                    && *last & PERIOD_FLAG == 0 =>
+            {
+                *last |= PERIOD_FLAG;
+            }
+            _ => {
+                let b = u8::try_from(character)
+                    .ok()
+                    .filter(|b| is_printable(*b))
+                    .ok_or_else(|| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            format!("Character '{character}' can not be displayed"),
+                        )
+                    })?;
+                buff.push(b);
+            }
         }
     }
-    Ok(result)
+    Ok(buff)
 }
 
-pub(crate) fn write_ch<A: Write + Seek>(
+/// Write a string, the counterpart of [`read_ch`].
+///
+/// A string that is shorter than the location is followed by zero bytes if the descriptor
+/// has a `null`, otherwise it is padded with spaces. For `null` `terminate` there always is
+/// room for at least one zero byte. Bits outside the `mask` keep their current value.
+///
+/// Text with [`UNDECODED`] characters is refused, write its raw bytes with
+/// [`write_ch_bytes`] instead.
+pub(crate) fn write_ch<A: Read + Write + Seek>(
     stream: &mut A,
-    location: u64,
-    length: usize,
-    value: String,
-    char_map: &Option<String>,
-    nibble: &Option<Nibble>,
+    location: Location,
+    value: &str,
+    mask: Option<u64>,
+    charset: &Charset,
+    nibble: Nibble,
+    null: Option<Null>,
 ) -> io::Result<()> {
-    stream.seek(SeekFrom::Start(location))?;
-    // if buffer contains non-ASCII characters, fail
-    if !value.is_ascii() {
+    if value.contains(UNDECODED) {
         return Err(io::Error::new(
             io::ErrorKind::InvalidInput,
-            format!("String is not ASCII: {value}"),
+            "Text with undecoded characters can not be written, write its raw bytes instead",
         ));
     }
-    let mut buff = value.as_bytes().to_vec();
-    if let Some(char_map) = char_map {
-        for b in buff.iter_mut() {
-            let idx = char_map.find(*b as char).unwrap_or(0);
-            *b = idx as u8;
-        }
-    }
-    if buff.len() > length {
+    let buff = match charset {
+        Charset::Map(char_map) => encode_char_map(value, char_map)?,
+        Charset::Ascii { period_flag } => encode_display_text(value, *period_flag)?,
+    };
+    write_ch_bytes(stream, location, &buff, mask, charset, nibble, null)
+}
+
+/// Write the bytes of a string as they are, e.g. the raw bytes of a
+/// [`NvValue::UndecodedText`], padded like [`write_ch`] does.
+pub(crate) fn write_ch_bytes<A: Read + Write + Seek>(
+    stream: &mut A,
+    location: Location,
+    bytes: &[u8],
+    mask: Option<u64>,
+    charset: &Charset,
+    nibble: Nibble,
+    null: Option<Null>,
+) -> io::Result<()> {
+    let length = location.len();
+    let capacity = if nibble == Nibble::Both {
+        length
+    } else {
+        length.div_ceil(2)
+    };
+    let max_length = if null == Some(Null::Terminate) {
+        capacity.saturating_sub(1)
+    } else {
+        capacity
+    };
+    if bytes.len() > max_length {
         return Err(io::Error::new(
             io::ErrorKind::InvalidInput,
-            format!("String is too long: {} > {}", buff.len(), length),
+            format!("String is too long: {} > {}", bytes.len(), max_length),
         ));
     }
+    let mut buff = bytes.to_vec();
+    if buff.len() < capacity {
+        let padding = match (null, charset) {
+            (Some(_), _) => 0,
+            (None, Charset::Ascii { .. }) => b' ',
+            (None, Charset::Map(char_map)) => encode_char_map(" ", char_map)?[0],
+        };
+        buff.resize(capacity, padding);
+    }
 
-    if let Some(nibble) = nibble {
-        buff = do_nibble(length, &buff, *nibble)?;
+    if let Some(mask) = mask {
+        let current = read_location(stream, &location)?;
+        let current = de_nibble(length, &current, nibble)?;
+        keep_unmasked_bits(&mut buff, &current, mask).map_err(|(b, mask)| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Character 0x{b:02X} does not fit in mask 0x{mask:02X}"),
+            )
+        })?;
     }
 
-    stream.write_all(&buff)?;
+    let buff = do_nibble(length, &buff, nibble)?;
+    write_location(stream, &location, &buff)
+}
+
+/// Copy the bits outside the byte `mask` from `current` into `buff`.
+///
+/// Fails with the byte and mask if a byte in `buff` has bits outside the mask.
+fn keep_unmasked_bits(buff: &mut [u8], current: &[u8], mask: u64) -> Result<(), (u8, u8)> {
+    let mask = mask as u8;
+    for (b, current) in buff.iter_mut().zip(current) {
+        if *b & !mask != 0 {
+            return Err((*b, mask));
+        }
+        *b |= current & !mask;
+    }
     Ok(())
 }
 
+/// The index of each character in the `char_map`
+fn encode_char_map(value: &str, char_map: &str) -> io::Result<Vec<u8>> {
+    value
+        .chars()
+        .map(|character| {
+            char_map
+                .chars()
+                .position(|c| c == character)
+                .and_then(|index| u8::try_from(index).ok())
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("Character '{character}' is not in the character map"),
+                    )
+                })
+        })
+        .collect()
+}
+
 /// Read a binary coded decimal number from the nvram file
 /// https://en.wikipedia.org/wiki/Binary-coded_decimal
 ///
@@ -212,12 +410,18 @@ pub(crate) fn write_ch<A: Write + Seek>(
 /// * `nvram_file` - The file to read from
 /// * `location` - The location in the file to start reading from
 /// * `length` - The number of bytes to read
+/// * `mask` - Applied to each byte before decoding the digits
+/// * `blank` - The nibble that is shown as an empty digit, read as 0
+///
+/// The value is returned without `scale`, see [`crate::value::apply_scale`]. Other nibbles above 9 are
+/// an error, they are usually a sign of a corrupted NVRAM.
 pub(crate) fn read_bcd<A: Read + Seek>(
     stream: &mut A,
     location: Location,
     nibble: Nibble,
-    scale: &Number,
     endian: Endian,
+    mask: Option<u64>,
+    blank: u8,
 ) -> io::Result<u64> {
     let mut buff = read_location(stream, &location)?;
 
@@ -229,76 +433,174 @@ pub(crate) fn read_bcd<A: Read + Seek>(
         buff = de_nibble(buff.len(), &buff, nibble)?;
     }
 
-    let mut vaue = 0;
-    for item in buff.iter() {
-        vaue *= 100;
-        vaue += cap_bcd(item & 0x0F) as u64;
-        vaue += cap_bcd((item & 0xF0) >> 4) as u64 * 10;
+    if let Some(mask) = mask {
+        for b in buff.iter_mut() {
+            *b &= mask as u8;
+        }
     }
-    Ok(apply_scale(scale, vaue))
+
+    buff.iter()
+        .try_fold(0u64, |value, item| -> io::Result<u64> {
+            let high = bcd_digit(item >> 4, blank)?;
+            let low = bcd_digit(item & 0x0F, blank)?;
+            let digits = u64::from(high * 10 + low);
+            value
+                .checked_mul(100)
+                .and_then(|value| value.checked_add(digits))
+                .ok_or_else(|| too_large(buff.len() * 2, "digits"))
+        })
 }
 
-fn apply_scale(scale: &Number, score: u64) -> u64 {
-    if scale.is_u64() {
-        (score as f64 * scale.as_u64().unwrap() as f64) as u64
-    } else {
-        (score as f64 * scale.as_f64().unwrap()) as u64
-    }
+fn too_large(count: usize, unit: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Value of {count} {unit} does not fit in 64 bits"),
+    )
 }
 
-/// Ignore nibbles 0xA to 0xF (0xF = blank on Dracula/Wild Fyre) (prefix)
-pub(crate) fn cap_bcd(value: u8) -> u8 {
-    if value > 9 { 0 } else { value }
+/// The value of a BCD digit, a `blank` digit (e.g. 0xF on Dracula/Wild Fyre) is 0
+fn bcd_digit(nibble: u8, blank: u8) -> io::Result<u8> {
+    match nibble {
+        0..=9 => Ok(nibble),
+        _ if nibble == blank => Ok(0),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Invalid BCD digit 0x{nibble:X}"),
+        )),
+    }
 }
 
-pub(crate) fn write_bcd<A: Write + Seek>(
+/// Write a binary coded decimal number without `scale`, the counterpart of [`read_bcd`].
+///
+/// Bits outside the `mask` keep their current value.
+pub(crate) fn write_bcd<A: Read + Write + Seek>(
     stream: &mut A,
-    location: u64,
-    length: usize,
+    location: Location,
     nibble: Nibble,
+    endian: Endian,
+    mask: Option<u64>,
     value: u64,
 ) -> io::Result<()> {
-    stream.seek(SeekFrom::Start(location))?;
-    // the nibble function will validate the length
-    let buff_len = if nibble == Nibble::Both {
+    let length = location.len();
+    let byte_count = if nibble == Nibble::Both {
         length
     } else {
         length.div_ceil(2)
     };
-    let mut buff = vec![0; buff_len];
-    let mut score = value;
-    for b in buff.iter_mut() {
-        *b = ((score % 10) + ((score / 10) << 4)) as u8;
-        score /= 100;
+    // a byte holds two digits, or one if only a nibble of each byte is used
+    let digits = if nibble == Nibble::Both {
+        length * 2
+    } else {
+        length
+    };
+    if u32::try_from(digits)
+        .ok()
+        .and_then(|digits| 10u64.checked_pow(digits))
+        .is_some_and(|limit| value >= limit)
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Value {value} does not fit in {digits} digits"),
+        ));
     }
-
-    if nibble != Nibble::Both {
-        buff = do_nibble(length, &buff, nibble)?;
+    let mut buff = vec![0; byte_count];
+    let mut remaining = value;
+    for b in buff.iter_mut().rev() {
+        *b = (((remaining / 10) % 10) << 4 | (remaining % 10)) as u8;
+        remaining /= 100;
     }
-
-    stream.write_all(&buff)?;
-    Ok(())
+    if let Some(mask) = mask {
+        let mut current = read_location(stream, &location)?;
+        if endian == Endian::Little {
+            current.reverse();
+        }
+        let current = de_nibble(length, &current, nibble)?;
+        keep_unmasked_bits(&mut buff, &current, mask).map_err(|_| mask_error(value, mask))?;
+    }
+    let mut buff = do_nibble(length, &buff, nibble)?;
+    if endian == Endian::Little {
+        buff.reverse();
+    }
+    write_location(stream, &location, &buff)
 }
 
+/// Read an unsigned integer, without `scale`, see [`crate::value::apply_scale`].
+///
+/// The `mask` is applied to the whole integer and the masked bits are not shifted, a
+/// flag in bit 2 with mask `0x04` is read as 0 or 4.
 pub(crate) fn read_int<T: Read + Seek>(
     nvram_file: &mut T,
     endian: Endian,
     nibble: Nibble,
     location: Location,
-    scale: &Number,
+    mask: Option<u64>,
 ) -> io::Result<u64> {
     let buff = read_location(nvram_file, &location)?;
-    let buff = de_nibble(buff.len(), &buff, nibble)?;
-    let score = match endian {
-        Endian::Big => buff
-            .iter()
-            .fold(0u64, |acc, &x| acc.wrapping_shl(8).wrapping_add(x as u64)),
-        Endian::Little => buff
-            .iter()
-            .rev()
-            .fold(0u64, |acc, &x| acc.wrapping_shl(8).wrapping_add(x as u64)),
+    let mut buff = de_nibble(buff.len(), &buff, nibble)?;
+    if endian == Endian::Little {
+        buff.reverse();
+    }
+    let value = buff
+        .iter()
+        .try_fold(0u64, |value, &byte| {
+            value.checked_mul(0x100)?.checked_add(u64::from(byte))
+        })
+        .ok_or_else(|| too_large(buff.len(), "bytes"))?;
+    Ok(value & mask.unwrap_or(u64::MAX))
+}
+
+/// Write an unscaled integer, the counterpart of [`read_int`].
+///
+/// Bits outside the `mask` keep their current value.
+pub(crate) fn write_int<T: Read + Write + Seek>(
+    nvram_file: &mut T,
+    endian: Endian,
+    nibble: Nibble,
+    location: Location,
+    mask: Option<u64>,
+    value: u64,
+) -> io::Result<()> {
+    let value = match mask {
+        Some(mask) if value & !mask != 0 => return Err(mask_error(value, mask)),
+        Some(mask) => {
+            let current = read_int(nvram_file, endian, nibble, location.clone(), None)?;
+            value | (current & !mask)
+        }
+        None => value,
+    };
+    let length = location.len();
+    let byte_count = if nibble == Nibble::Both {
+        length
+    } else {
+        length.div_ceil(2)
     };
-    Ok(apply_scale(scale, score))
+    let bits = if nibble == Nibble::Both {
+        length * 8
+    } else {
+        length * 4
+    } as u32;
+    if bits < u64::BITS && value >> bits != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Value {value} does not fit in {length} bytes"),
+        ));
+    }
+    let mut buff: Vec<u8> = (0..byte_count)
+        .rev()
+        .map(|i| value.checked_shr(i as u32 * 8).unwrap_or(0) as u8)
+        .collect();
+    if endian == Endian::Little {
+        buff.reverse();
+    }
+    let buff = do_nibble(length, &buff, nibble)?;
+    write_location(nvram_file, &location, &buff)
+}
+
+fn mask_error(value: u64, mask: u64) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("Value {value} does not fit in mask 0x{mask:X}"),
+    )
 }
 
 pub(crate) fn read_exact_at<A: Seek + Read>(
@@ -326,27 +628,62 @@ pub(crate) fn read_exact_at<A: Seek + Read>(
 /// * two-byte year (2015 is 0x07 0xDF),
 /// * month (1-12),
 /// * day of month (1-31),
-/// * day of the week (0-6, 0=Sunday),
+/// * day of the week (1-7, 1=Sunday),
 /// * hour (0-23)
 /// * minute (0-59).
 ///
+/// The bytes are returned as stored, see [`Timestamp::is_valid`].
 pub(crate) fn read_wpc_rtc<T: Read + Seek>(
     nvram_file: &mut T,
-    start: u64,
-    length: usize,
-) -> io::Result<String> {
-    let mut buff = vec![0; length];
-    read_exact_at(nvram_file, start, &mut buff)?;
-    let year = ((buff[0] as u16) << 8) | buff[1] as u16;
-    let month = buff[2];
-    let day = buff[3];
-    let _dow = buff[4];
-    let hour = buff[5];
-    let minute = buff[6];
-    // output as "YYYY-MM-DD HH:MM"
-    Ok(format!(
-        "{year:04}-{month:02}-{day:02} {hour:02}:{minute:02}"
-    ))
+    location: &Location,
+) -> io::Result<Timestamp> {
+    let buff = read_location(nvram_file, location)?;
+    let [year_high, year_low, month, day, weekday, hour, minute] = buff[..] else {
+        return Err(wpc_rtc_length_error(buff.len()));
+    };
+    Ok(Timestamp {
+        year: u16::from_be_bytes([year_high, year_low]),
+        month,
+        day,
+        weekday,
+        hour,
+        minute,
+    })
+}
+
+/// Write a real-time clock value, the counterpart of [`read_wpc_rtc`].
+pub(crate) fn write_wpc_rtc<T: Write + Seek>(
+    nvram_file: &mut T,
+    location: &Location,
+    timestamp: &Timestamp,
+) -> io::Result<()> {
+    if location.len() != 7 {
+        return Err(wpc_rtc_length_error(location.len()));
+    }
+    if !timestamp.is_valid() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid timestamp {timestamp:?}"),
+        ));
+    }
+    let [year_high, year_low] = timestamp.year.to_be_bytes();
+    let buff = [
+        year_high,
+        year_low,
+        timestamp.month,
+        timestamp.day,
+        timestamp.weekday,
+        timestamp.hour,
+        timestamp.minute,
+    ];
+    write_location(nvram_file, location, &buff)
+}
+
+fn wpc_rtc_length_error(length: usize) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("wpc_rtc encoding needs 7 bytes, got {length}"),
+    )
 }
 
 pub(crate) fn read_bool<T: Read + Seek>(
@@ -354,15 +691,10 @@ pub(crate) fn read_bool<T: Read + Seek>(
     nibble: Nibble,
     endian: Endian,
     location: Location,
+    mask: Option<u64>,
     invert: bool,
 ) -> io::Result<bool> {
-    let value = read_int(
-        nvram_file,
-        endian,
-        nibble,
-        location,
-        &Number::from(DEFAULT_SCALE),
-    )?;
+    let value = read_int(nvram_file, endian, nibble, location, mask)?;
     let bool_value = if invert { value == 0 } else { value != 0 };
     Ok(bool_value)
 }
@@ -370,7 +702,9 @@ pub(crate) fn read_bool<T: Read + Seek>(
 #[cfg(test)]
 mod tests {
     use crate::encoding::*;
-    use crate::model::{DEFAULT_SCALE, Endian, Nibble};
+    use crate::model::{DEFAULT_BCD_BLANK, Endian, Nibble};
+    use crate::value::apply_scale;
+    use serde_json::Number;
     use std::io;
 
     #[test]
@@ -384,31 +718,239 @@ mod tests {
             &mut cursor,
             location,
             Nibble::Both,
-            &Number::from(1),
             Endian::Big,
+            None,
+            DEFAULT_BCD_BLANK,
         )?;
         pretty_assertions::assert_eq!(score, 1_234_567_890);
         Ok(())
     }
 
     #[test]
-    fn test_read_bcd_ignore_a_to_f() -> io::Result<()> {
-        let data = vec![0x0, 0xFF];
-        let mut cursor = io::Cursor::new(data);
+    fn test_read_bcd_rejects_a_to_e() -> io::Result<()> {
+        let read = |byte: u8| {
+            read_bcd(
+                &mut io::Cursor::new(vec![0x0, byte]),
+                Location::Continuous {
+                    start: 1,
+                    length: 1,
+                },
+                Nibble::High,
+                Endian::Little,
+                None,
+                DEFAULT_BCD_BLANK,
+            )
+        };
+        for digit in 0xA..=0xE {
+            let result = read(digit << 4);
+            pretty_assertions::assert_eq!(
+                result.unwrap_err().to_string(),
+                format!("Invalid BCD digit 0x{digit:X}")
+            );
+        }
+        // 0xF is the blank digit
+        pretty_assertions::assert_eq!(read(0xFF)?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_bcd() -> io::Result<()> {
+        // bytes, nibble, endian
+        let cases = [
+            (vec![0x00, 0x12, 0x34], Nibble::Both, Endian::Big),
+            (vec![0x34, 0x12, 0x00], Nibble::Both, Endian::Little),
+            (
+                vec![0x00, 0x00, 0x01, 0x02, 0x03, 0x04],
+                Nibble::Low,
+                Endian::Big,
+            ),
+            (
+                vec![0x40, 0x30, 0x20, 0x10, 0x00, 0x00],
+                Nibble::High,
+                Endian::Little,
+            ),
+        ];
+        for (expected, nibble, endian) in cases {
+            let mut cursor = io::Cursor::new(vec![0xFF; expected.len()]);
+            let location = || Location::Continuous {
+                start: 0,
+                length: expected.len(),
+            };
+            write_bcd(&mut cursor, location(), nibble, endian, None, 1234)?;
+            pretty_assertions::assert_eq!(cursor.get_ref(), &expected, "{nibble:?} {endian:?}");
+            let value = read_bcd(
+                &mut cursor,
+                location(),
+                nibble,
+                endian,
+                None,
+                DEFAULT_BCD_BLANK,
+            )?;
+            pretty_assertions::assert_eq!(value, 1234, "{nibble:?} {endian:?}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_bcd_scattered() -> io::Result<()> {
+        let mut cursor = io::Cursor::new(vec![0xFF; 4]);
+        let location = || Location::Scattered {
+            offsets: vec![0, 2],
+        };
+        write_bcd(
+            &mut cursor,
+            location(),
+            Nibble::Both,
+            Endian::Big,
+            None,
+            1234,
+        )?;
+        pretty_assertions::assert_eq!(cursor.get_ref(), &vec![0x12, 0xFF, 0x34, 0xFF]);
         let value = read_bcd(
             &mut cursor,
-            Location::Continuous {
-                start: 1,
-                length: 1,
-            },
-            Nibble::High,
-            &Number::from(1),
-            Endian::Little,
+            location(),
+            Nibble::Both,
+            Endian::Big,
+            None,
+            DEFAULT_BCD_BLANK,
+        )?;
+        pretty_assertions::assert_eq!(value, 1234);
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_bcd_overflow() {
+        let mut cursor = io::Cursor::new(vec![0x00; 2]);
+        let location = Location::Continuous {
+            start: 0,
+            length: 2,
+        };
+        let result = write_bcd(
+            &mut cursor,
+            location,
+            Nibble::Both,
+            Endian::Big,
+            None,
+            10_000,
+        );
+        pretty_assertions::assert_eq!(
+            result.unwrap_err().to_string(),
+            "Value 10000 does not fit in 4 digits"
+        );
+        pretty_assertions::assert_eq!(cursor.into_inner(), vec![0x00, 0x00]);
+
+        // three low nibbles hold three digits, not the four of the two bytes they make up
+        let mut cursor = io::Cursor::new(vec![0x00; 3]);
+        let location = || Location::Continuous {
+            start: 0,
+            length: 3,
+        };
+        let result = write_bcd(
+            &mut cursor,
+            location(),
+            Nibble::Low,
+            Endian::Big,
+            None,
+            1000,
+        );
+        pretty_assertions::assert_eq!(
+            result.unwrap_err().to_string(),
+            "Value 1000 does not fit in 3 digits"
+        );
+        write_bcd(&mut cursor, location(), Nibble::Low, Endian::Big, None, 999).unwrap();
+        pretty_assertions::assert_eq!(cursor.into_inner(), vec![0x09, 0x09, 0x09]);
+    }
+
+    #[test]
+    fn test_read_bcd_large_score() -> io::Result<()> {
+        // 12 digits, more than f64 can scale without rounding when multiplied by 10
+        let mut cursor = io::Cursor::new(vec![0x98, 0x76, 0x54, 0x32, 0x10, 0x99]);
+        let location = Location::Continuous {
+            start: 0,
+            length: 6,
+        };
+        let score = read_bcd(
+            &mut cursor,
+            location,
+            Nibble::Both,
+            Endian::Big,
+            None,
+            DEFAULT_BCD_BLANK,
+        )?;
+        pretty_assertions::assert_eq!(score, 987_654_321_099);
+        pretty_assertions::assert_eq!(apply_scale(&Number::from(10), score)?, 9_876_543_210_990);
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_bcd_overflow() {
+        let mut cursor = io::Cursor::new(vec![0x99; 10]);
+        let location = Location::Continuous {
+            start: 0,
+            length: 10,
+        };
+        let result = read_bcd(
+            &mut cursor,
+            location,
+            Nibble::Both,
+            Endian::Big,
+            None,
+            DEFAULT_BCD_BLANK,
+        );
+        pretty_assertions::assert_eq!(
+            result.unwrap_err().to_string(),
+            "Value of 20 digits does not fit in 64 bits"
+        );
+    }
+
+    #[test]
+    fn test_read_bcd_blank_and_invalid_digits() -> io::Result<()> {
+        let location = || Location::Continuous {
+            start: 0,
+            length: 3,
+        };
+        let mut blank = io::Cursor::new(vec![0xFF, 0xF1, 0x20]);
+        let value = read_bcd(&mut blank, location(), Nibble::Both, Endian::Big, None, 0xF)?;
+        pretty_assertions::assert_eq!(value, 120);
+
+        let mut corrupted = io::Cursor::new(vec![0x00, 0x1C, 0x20]);
+        let result = read_bcd(
+            &mut corrupted,
+            location(),
+            Nibble::Both,
+            Endian::Big,
+            None,
+            0xF,
+        );
+        pretty_assertions::assert_eq!(result.unwrap_err().to_string(), "Invalid BCD digit 0xC");
+
+        let mut other_blank = io::Cursor::new(vec![0xAA, 0xA1, 0x20]);
+        let value = read_bcd(
+            &mut other_blank,
+            location(),
+            Nibble::Both,
+            Endian::Big,
+            None,
+            0xA,
         )?;
-        pretty_assertions::assert_eq!(value, 0);
+        pretty_assertions::assert_eq!(value, 120);
         Ok(())
     }
 
+    #[test]
+    fn test_read_int_overflow() {
+        let mut cursor = io::Cursor::new(vec![0x01; 9]);
+        let location = Location::Continuous {
+            start: 0,
+            length: 9,
+        };
+        let result = read_int(&mut cursor, Endian::Big, Nibble::Both, location, None);
+        pretty_assertions::assert_eq!(
+            result.unwrap_err().to_string(),
+            "Value of 9 bytes does not fit in 64 bits"
+        );
+    }
+
     #[test]
     fn test_read_int() -> io::Result<()> {
         let data = vec![0x0, 0xFF];
@@ -421,7 +963,7 @@ mod tests {
                 start: 1,
                 length: 1,
             },
-            &Number::from(DEFAULT_SCALE),
+            None,
         )?;
         pretty_assertions::assert_eq!(value, 255);
         Ok(())
@@ -439,12 +981,160 @@ mod tests {
                 start: 1,
                 length: 1,
             },
-            &Number::from(DEFAULT_SCALE),
+            None,
         )?;
         pretty_assertions::assert_eq!(value, 15);
         Ok(())
     }
 
+    #[test]
+    fn test_write_int() -> io::Result<()> {
+        let mut cursor = io::Cursor::new(vec![0; 4]);
+        let location = Location::Continuous {
+            start: 1,
+            length: 2,
+        };
+        write_int(
+            &mut cursor,
+            Endian::Big,
+            Nibble::Both,
+            location,
+            None,
+            0x1234,
+        )?;
+        assert_eq!(cursor.get_ref(), &vec![0x00, 0x12, 0x34, 0x00]);
+        let location = Location::Scattered {
+            offsets: vec![3, 0],
+        };
+        write_int(
+            &mut cursor,
+            Endian::Little,
+            Nibble::Both,
+            location,
+            None,
+            0xABCD,
+        )?;
+        assert_eq!(cursor.get_ref(), &vec![0xAB, 0x12, 0x34, 0xCD]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_masked_int() -> io::Result<()> {
+        let location = || Location::Continuous {
+            start: 0,
+            length: 2,
+        };
+        let mut cursor = io::Cursor::new(vec![0xA5, 0x5A]);
+        let value = read_int(
+            &mut cursor,
+            Endian::Big,
+            Nibble::Both,
+            location(),
+            Some(0x0FF0),
+        )?;
+        assert_eq!(value, 0x0550);
+        write_int(
+            &mut cursor,
+            Endian::Big,
+            Nibble::Both,
+            location(),
+            Some(0x0FF0),
+            0x0120,
+        )?;
+        assert_eq!(cursor.get_ref(), &vec![0xA1, 0x2A]);
+        let result = write_int(
+            &mut cursor,
+            Endian::Big,
+            Nibble::Both,
+            location(),
+            Some(0x0FF0),
+            0x1000,
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Value 4096 does not fit in mask 0xFF0"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_int_high_nibble() -> io::Result<()> {
+        let mut cursor = io::Cursor::new(vec![0; 2]);
+        let location = Location::Continuous {
+            start: 0,
+            length: 2,
+        };
+        write_int(&mut cursor, Endian::Big, Nibble::High, location, None, 0x12)?;
+        assert_eq!(cursor.get_ref(), &vec![0x10, 0x20]);
+        let location = Location::Continuous {
+            start: 0,
+            length: 2,
+        };
+        let result = write_int(
+            &mut cursor,
+            Endian::Big,
+            Nibble::High,
+            location,
+            None,
+            0x123,
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Value 291 does not fit in 2 bytes"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_wpc_rtc_scattered() -> io::Result<()> {
+        let mut cursor = io::Cursor::new(vec![
+            0x07, 0x00, 0xE8, 0x00, 0x06, 0x00, 0x13, 0x00, 0x04, 0x00, 0x10, 0x00, 0x2A,
+        ]);
+        let location = Location::Scattered {
+            offsets: vec![0, 2, 4, 6, 8, 10, 12],
+        };
+        let timestamp = read_wpc_rtc(&mut cursor, &location)?;
+        assert_eq!(Timestamp::new(2024, 6, 19, 16, 42), Some(timestamp));
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_wpc_rtc_wrong_length() {
+        let mut cursor = io::Cursor::new(vec![0x07, 0xE8, 0x06, 0x13]);
+        let location = Location::Continuous {
+            start: 0,
+            length: 4,
+        };
+        let result = read_wpc_rtc(&mut cursor, &location);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "wpc_rtc encoding needs 7 bytes, got 4"
+        );
+    }
+
+    #[test]
+    fn test_write_wpc_rtc() -> io::Result<()> {
+        let mut cursor = io::Cursor::new(vec![0xFF; 8]);
+        let location = Location::Continuous {
+            start: 1,
+            length: 7,
+        };
+        let timestamp = Timestamp::new(2023, 11, 7, 0, 14).unwrap();
+        write_wpc_rtc(&mut cursor, &location, &timestamp)?;
+        assert_eq!(
+            cursor.get_ref(),
+            &vec![0xFF, 0x07, 0xE7, 0x0B, 0x07, 0x03, 0x00, 0x0E]
+        );
+        assert_eq!(timestamp, read_wpc_rtc(&mut cursor, &location)?);
+
+        let unset = Timestamp {
+            month: 0xFF,
+            ..timestamp
+        };
+        assert!(write_wpc_rtc(&mut cursor, &location, &unset).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_read_bool_continuous() -> io::Result<()> {
         let mut cursor = io::Cursor::new(vec![0x00, 0x01]);
@@ -456,6 +1146,7 @@ mod tests {
                 start: 1,
                 length: 1,
             },
+            None,
             false,
         )?;
         pretty_assertions::assert_eq!(value, true);
@@ -471,6 +1162,7 @@ mod tests {
             Nibble::Both,
             Endian::Big,
             Location::Scattered { offsets: vec![2] },
+            None,
             false,
         )?;
         pretty_assertions::assert_eq!(value, true);
@@ -486,12 +1178,20 @@ mod tests {
             Nibble::Both,
             Endian::Big,
             Location::Scattered { offsets: vec![3] },
+            None,
             true,
         )?;
         pretty_assertions::assert_eq!(value, true);
         Ok(())
     }
 
+    const ASCII: Charset = Charset::Ascii { period_flag: false };
+    const DISPLAY: Charset = Charset::Ascii { period_flag: true };
+
+    fn text(text: &str) -> NvValue {
+        NvValue::Text(text.to_string())
+    }
+
     #[test]
     fn test_read_ch() -> io::Result<()> {
         let mut cursor = io::Cursor::new(vec![0x41, 0x42, 0x43, 0x44, 0x45]);
@@ -502,17 +1202,17 @@ mod tests {
                 length: 5,
             },
             None,
-            &None,
+            &ASCII,
             Nibble::Both,
             None,
         )?;
-        pretty_assertions::assert_eq!(score, "ABCDE");
+        pretty_assertions::assert_eq!(score, text("ABCDE"));
         Ok(())
     }
 
     #[test]
     fn test_read_ch_with_charmap() -> io::Result<()> {
-        let char_map = Some("???????????ABCDEFGHIJKLMNOPQRSTUVWXYZ".to_string());
+        let char_map = Charset::Map("???????????ABCDEFGHIJKLMNOPQRSTUVWXYZ".to_string());
         let mut cursor = io::Cursor::new(vec![0x0B, 0x0C, 0x0D, 0x0E, 0x0F]);
         let score = read_ch(
             &mut cursor,
@@ -525,34 +1225,496 @@ mod tests {
             Nibble::Both,
             None,
         )?;
-        pretty_assertions::assert_eq!(score, "ABCDE");
+        pretty_assertions::assert_eq!(score, text("ABCDE"));
         Ok(())
     }
 
     #[test]
     fn test_write_ch() -> io::Result<()> {
         let mut cursor = io::Cursor::new(vec![0x00, 0x00, 0x00, 0x00, 0x00]);
-        write_ch(&mut cursor, 0x0000, 5, "ABCDE".to_string(), &None, &None)?;
+        write_ch(
+            &mut cursor,
+            continuous(0, 5),
+            "ABCDE",
+            None,
+            &ASCII,
+            Nibble::Both,
+            None,
+        )?;
         pretty_assertions::assert_eq!(cursor.into_inner(), vec![0x41, 0x42, 0x43, 0x44, 0x45]);
         Ok(())
     }
 
+    fn continuous(start: u64, length: usize) -> Location {
+        Location::Continuous { start, length }
+    }
+
+    #[test]
+    fn test_write_ch_padding() -> io::Result<()> {
+        let write = |null: Option<Null>| -> io::Result<Vec<u8>> {
+            let mut cursor = io::Cursor::new(vec![0xFF; 4]);
+            write_ch(
+                &mut cursor,
+                continuous(0, 4),
+                "AB",
+                None,
+                &ASCII,
+                Nibble::Both,
+                null,
+            )?;
+            Ok(cursor.into_inner())
+        };
+        pretty_assertions::assert_eq!(write(None)?, vec![0x41, 0x42, 0x20, 0x20]);
+        pretty_assertions::assert_eq!(write(Some(Null::Ignore))?, vec![0x41, 0x42, 0x00, 0x00]);
+        pretty_assertions::assert_eq!(write(Some(Null::Truncate))?, vec![0x41, 0x42, 0x00, 0x00]);
+        pretty_assertions::assert_eq!(write(Some(Null::Terminate))?, vec![0x41, 0x42, 0x00, 0x00]);
+
+        let mut cursor = io::Cursor::new(vec![0xFF; 4]);
+        write_ch(
+            &mut cursor,
+            continuous(0, 4),
+            "ABCD",
+            None,
+            &ASCII,
+            Nibble::Both,
+            Some(Null::Truncate),
+        )?;
+        pretty_assertions::assert_eq!(cursor.get_ref(), &b"ABCD".to_vec());
+        let result = write_ch(
+            &mut cursor,
+            continuous(0, 4),
+            "ABCD",
+            None,
+            &ASCII,
+            Nibble::Both,
+            Some(Null::Terminate),
+        );
+        pretty_assertions::assert_eq!(result.unwrap_err().to_string(), "String is too long: 4 > 3");
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_ch_scattered_nibble_mask() -> io::Result<()> {
+        // "AB" in the low nibbles of every other byte, the high bit of each character is kept
+        let mut cursor = io::Cursor::new(vec![0x00, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+        let location = Location::Scattered {
+            offsets: vec![1, 3, 5, 7],
+        };
+        write_ch(
+            &mut cursor,
+            location,
+            "AB",
+            Some(0x7F),
+            &ASCII,
+            Nibble::Low,
+            None,
+        )?;
+        pretty_assertions::assert_eq!(
+            cursor.get_ref(),
+            &vec![0x00, 0x0C, 0x00, 0x01, 0x00, 0x04, 0x00, 0x02]
+        );
+        let location = Location::Scattered {
+            offsets: vec![1, 3, 5, 7],
+        };
+        let text = read_ch(&mut cursor, location, Some(0x7F), &ASCII, Nibble::Low, None)?;
+        pretty_assertions::assert_eq!(text, self::text("AB"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_ch_unrepresentable() {
+        let char_map = Charset::Map(" ABC".to_string());
+        let mut cursor = io::Cursor::new(vec![0; 3]);
+        let result = write_ch(
+            &mut cursor,
+            continuous(0, 3),
+            "ABX",
+            None,
+            &char_map,
+            Nibble::Both,
+            None,
+        );
+        pretty_assertions::assert_eq!(
+            result.unwrap_err().to_string(),
+            "Character 'X' is not in the character map"
+        );
+        let result = write_ch(
+            &mut cursor,
+            continuous(0, 1),
+            "A.",
+            Some(0x7F),
+            &DISPLAY,
+            Nibble::Both,
+            None,
+        );
+        pretty_assertions::assert_eq!(
+            result.unwrap_err().to_string(),
+            "Character 0xC1 does not fit in mask 0x7F"
+        );
+    }
+
     #[test]
     fn test_write_ch_with_charmap() -> io::Result<()> {
-        let char_map = Some("???????????ABCDEFGHIJKLMNOPQRSTUVWXYZ".to_string());
+        let char_map = Charset::Map("???????????ABCDEFGHIJKLMNOPQRSTUVWXYZ".to_string());
         let mut cursor = io::Cursor::new(vec![0x00, 0x00, 0x00, 0x00, 0x00]);
         write_ch(
             &mut cursor,
-            0x0000,
-            5,
-            "ABCDE".to_string(),
+            continuous(0, 5),
+            "ABCDE",
+            None,
             &char_map,
-            &None,
+            Nibble::Both,
+            None,
         )?;
         pretty_assertions::assert_eq!(cursor.into_inner(), vec![0x0B, 0x0C, 0x0D, 0x0E, 0x0F]);
         Ok(())
     }
 
+    #[test]
+    fn test_read_ch_period_flag() -> io::Result<()> {
+        // "AT HIGH SPEED. " from High Speed, with 0xC4 for "D."
+        let mut bytes = b"AT HIGH SPEE".to_vec();
+        bytes.extend([0xC4, 0x20, 0xFF]);
+        let mut cursor = io::Cursor::new(bytes.clone());
+        let value = read_ch(
+            &mut cursor,
+            continuous(0, 15),
+            None,
+            &DISPLAY,
+            Nibble::Both,
+            None,
+        )?;
+        pretty_assertions::assert_eq!(
+            value,
+            NvValue::UndecodedText {
+                text: "AT HIGH SPEED. \u{FFFD}".to_string(),
+                raw: bytes.clone(),
+            }
+        );
+        let value = read_ch(
+            &mut cursor,
+            continuous(0, 15),
+            None,
+            &ASCII,
+            Nibble::Both,
+            None,
+        )?;
+        pretty_assertions::assert_eq!(value.to_string(), "AT HIGH SPEE\u{FFFD} \u{FFFD}");
+
+        // the text can not be written back, the raw bytes can
+        let mut written = io::Cursor::new(vec![0; 15]);
+        let result = write_ch(
+            &mut written,
+            continuous(0, 15),
+            "AT HIGH SPEED. \u{FFFD}",
+            None,
+            &DISPLAY,
+            Nibble::Both,
+            None,
+        );
+        pretty_assertions::assert_eq!(
+            result.unwrap_err().to_string(),
+            "Text with undecoded characters can not be written, write its raw bytes instead"
+        );
+        write_ch_bytes(
+            &mut written,
+            continuous(0, 15),
+            &bytes,
+            None,
+            &DISPLAY,
+            Nibble::Both,
+            None,
+        )?;
+        pretty_assertions::assert_eq!(written.into_inner(), bytes);
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_ch_period_flag() -> io::Result<()> {
+        let mut cursor = io::Cursor::new(vec![0; 4]);
+        write_ch(
+            &mut cursor,
+            continuous(0, 4),
+            "MR.X",
+            None,
+            &DISPLAY,
+            Nibble::Both,
+            None,
+        )?;
+        pretty_assertions::assert_eq!(cursor.get_ref(), &vec![0x4D, 0xD2, 0x58, 0x20]);
+        let value = read_ch(
+            &mut cursor,
+            continuous(0, 4),
+            None,
+            &DISPLAY,
+            Nibble::Both,
+            None,
+        )?;
+        pretty_assertions::assert_eq!(value, text("MR.X "));
+        // a leading period has no character to light the segment of
+        write_ch(
+            &mut cursor,
+            continuous(0, 4),
+            ".5,",
+            None,
+            &DISPLAY,
+            Nibble::Both,
+            None,
+        )?;
+        pretty_assertions::assert_eq!(cursor.get_ref(), &vec![0x2E, 0xB5, 0x20, 0x20]);
+        let value = read_ch(
+            &mut cursor,
+            continuous(0, 4),
+            None,
+            &DISPLAY,
+            Nibble::Both,
+            None,
+        )?;
+        pretty_assertions::assert_eq!(value, text(".5.  "));
+        write_ch(
+            &mut cursor,
+            continuous(0, 4),
+            "A.B,CD",
+            None,
+            &DISPLAY,
+            Nibble::Both,
+            None,
+        )?;
+        pretty_assertions::assert_eq!(cursor.get_ref(), &vec![0xC1, 0xC2, 0x43, 0x44]);
+
+        let result = write_ch(
+            &mut cursor,
+            continuous(0, 4),
+            "\u{C4}",
+            None,
+            &DISPLAY,
+            Nibble::Both,
+            None,
+        );
+        pretty_assertions::assert_eq!(
+            result.unwrap_err().to_string(),
+            "Character '\u{C4}' can not be displayed"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_ch_literal_period() -> io::Result<()> {
+        // without the period flag a period is a character of its own
+        let mut cursor = io::Cursor::new(vec![0; 5]);
+        write_ch(
+            &mut cursor,
+            continuous(0, 5),
+            "A.B,C",
+            None,
+            &ASCII,
+            Nibble::Both,
+            None,
+        )?;
+        pretty_assertions::assert_eq!(cursor.get_ref(), &vec![0x41, 0x2E, 0x42, 0x2C, 0x43]);
+        let value = read_ch(
+            &mut cursor,
+            continuous(0, 5),
+            None,
+            &ASCII,
+            Nibble::Both,
+            None,
+        )?;
+        pretty_assertions::assert_eq!(value, text("A.B,C"));
+
+        let mut cursor = io::Cursor::new(vec![0xC1]);
+        let value = read_ch(
+            &mut cursor,
+            continuous(0, 1),
+            None,
+            &ASCII,
+            Nibble::Both,
+            None,
+        )?;
+        pretty_assertions::assert_eq!(
+            value,
+            NvValue::UndecodedText {
+                text: "\u{FFFD}".to_string(),
+                raw: vec![0xC1],
+            }
+        );
+        Ok(())
+    }
+
+    /// bytes, nibble, mask, charset, null, expected
+    type ChCase<'a> = (
+        Vec<u8>,
+        Nibble,
+        Option<u64>,
+        &'a Charset,
+        Option<Null>,
+        NvValue,
+    );
+
+    #[test]
+    fn test_read_ch_combinations() -> io::Result<()> {
+        let char_map = Charset::Map(" ABC".to_string());
+        let cases: Vec<ChCase> = vec![
+            (
+                vec![0x41, 0x00, 0x42],
+                Nibble::Both,
+                None,
+                &ASCII,
+                None,
+                NvValue::UndecodedText {
+                    text: "A\u{FFFD}B".to_string(),
+                    raw: vec![0x41, 0x00, 0x42],
+                },
+            ),
+            (
+                vec![0x41, 0x00, 0x42],
+                Nibble::Both,
+                None,
+                &ASCII,
+                Some(Null::Ignore),
+                text("AB"),
+            ),
+            (
+                vec![0x41, 0x00, 0x42],
+                Nibble::Both,
+                None,
+                &ASCII,
+                Some(Null::Truncate),
+                text("A"),
+            ),
+            (
+                vec![0x41, 0x00, 0x42],
+                Nibble::Both,
+                None,
+                &ASCII,
+                Some(Null::Terminate),
+                text("A"),
+            ),
+            (
+                vec![0xC1, 0x42],
+                Nibble::Both,
+                Some(0x7F),
+                &ASCII,
+                None,
+                text("AB"),
+            ),
+            // the mask is applied before looking for the null
+            (
+                vec![0x80, 0x41],
+                Nibble::Both,
+                Some(0x7F),
+                &ASCII,
+                Some(Null::Ignore),
+                text("A"),
+            ),
+            (
+                vec![0x01, 0x00, 0x03],
+                Nibble::Both,
+                None,
+                &char_map,
+                Some(Null::Ignore),
+                text("AC"),
+            ),
+            (
+                vec![0x01, 0x00, 0x03],
+                Nibble::Both,
+                None,
+                &char_map,
+                Some(Null::Terminate),
+                text("A"),
+            ),
+            (
+                vec![0x01, 0x00, 0x03],
+                Nibble::Both,
+                None,
+                &char_map,
+                None,
+                text("A C"),
+            ),
+            (
+                vec![0x81, 0x02],
+                Nibble::Both,
+                Some(0x0F),
+                &char_map,
+                None,
+                text("AB"),
+            ),
+            (
+                vec![0x04, 0x01, 0x00, 0x00, 0x04, 0x02],
+                Nibble::Low,
+                None,
+                &ASCII,
+                None,
+                NvValue::UndecodedText {
+                    text: "A\u{FFFD}B".to_string(),
+                    raw: vec![0x41, 0x00, 0x42],
+                },
+            ),
+            (
+                vec![0x04, 0x01, 0x00, 0x00, 0x04, 0x02],
+                Nibble::Low,
+                None,
+                &ASCII,
+                Some(Null::Ignore),
+                text("AB"),
+            ),
+            (
+                vec![0x04, 0x01, 0x00, 0x00, 0x04, 0x02],
+                Nibble::Low,
+                None,
+                &ASCII,
+                Some(Null::Terminate),
+                text("A"),
+            ),
+            (
+                vec![0x40, 0x10, 0xC0, 0x20],
+                Nibble::High,
+                None,
+                &DISPLAY,
+                None,
+                text("AB."),
+            ),
+            (
+                vec![0xC0, 0x10, 0x40, 0x20],
+                Nibble::High,
+                Some(0x7F),
+                &ASCII,
+                None,
+                text("AB"),
+            ),
+            (
+                vec![0x00, 0x01, 0x00, 0x03],
+                Nibble::Low,
+                None,
+                &char_map,
+                None,
+                text("AC"),
+            ),
+            (
+                vec![0x00, 0x01, 0x00, 0x00, 0x00, 0x03],
+                Nibble::Low,
+                None,
+                &char_map,
+                Some(Null::Truncate),
+                text("A"),
+            ),
+        ];
+        for (bytes, nibble, mask, charset, null, expected) in cases {
+            let location = Location::Continuous {
+                start: 0,
+                length: bytes.len(),
+            };
+            let mut cursor = io::Cursor::new(bytes.clone());
+            let value = read_ch(&mut cursor, location, mask, charset, nibble, null)?;
+            pretty_assertions::assert_eq!(
+                value,
+                expected,
+                "{bytes:02X?} {nibble:?} {mask:?} {charset:?} {null:?}"
+            );
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_read_ch_with_nibble() -> io::Result<()> {
         // Nibble: where the sequence 0x04 0x01 0x04 0x02 0x04 0x03
@@ -565,11 +1727,11 @@ mod tests {
                 length: 6,
             },
             None,
-            &None,
+            &ASCII,
             Nibble::Low,
             None,
         )?;
-        pretty_assertions::assert_eq!(score, "ABC");
+        pretty_assertions::assert_eq!(score, text("ABC"));
         Ok(())
     }
 
@@ -583,12 +1745,12 @@ mod tests {
                 length: 5,
             },
             None,
-            &None,
+            &ASCII,
             Nibble::Both,
             Some(Null::Terminate),
         )
         .unwrap();
-        pretty_assertions::assert_eq!(score, "ABC");
+        pretty_assertions::assert_eq!(score, text("ABC"));
     }
 
     #[test]
@@ -2,28 +2,41 @@ pub mod checksum;
 pub mod dips;
 mod encoding;
 mod index;
-mod model;
+mod memory;
+pub mod model;
 pub mod resolve;
+#[cfg(test)]
+mod test_util;
+pub mod value;
 
-use crate::checksum::{ChecksumMismatch, update_all_checksum16, verify_all_checksum16};
-use crate::dips::{MAX_SWITCH_COUNT, get_dip_switch, set_dip_switch, validate_dip_switch_range};
-use crate::encoding::{
-    Location, read_bcd, read_bool, read_ch, read_int, read_wpc_rtc, write_bcd, write_ch,
+use crate::checksum::{
+    ChecksumMismatch, update_all_checksum8, update_all_checksum16, verify_all_checksum16,
+};
+use crate::dips::{
+    DIP_SWITCH_BYTES, MAX_SWITCH_COUNT, get_dip_switch, set_dip_switch, validate_dip_switch_range,
 };
+use crate::encoding::{Charset, Location, read_ch};
 use crate::index::get_index_map;
+use crate::memory::{LocateResult, Memory, locate};
 use crate::model::{
-    DEFAULT_INVERT, DEFAULT_LENGTH, DEFAULT_SCALE, Descriptor, Encoding, Endian, GlobalSettings,
-    HexOrInteger, MemoryLayoutType, Nibble, NvramMap, Platform, StateOrStateList,
+    DEFAULT_LENGTH, Descriptor, Encoding, GlobalSettings, HexOrInteger, Nibble, NvramMap, Platform,
+    StateOrStateList,
 };
+use crate::resolve::{
+    ResolveWarning, default_value, descriptor_values, descriptors, find_descriptor, resolve_value,
+    write_value,
+};
+use crate::value::{NvValue, Timestamp};
 use include_dir::{Dir, File, include_dir};
 use serde::de;
 use serde::de::DeserializeOwned;
-use serde_json::{Number, Value};
+use serde_json::Value;
 use std::collections::HashMap;
 use std::fs::OpenOptions;
 use std::io;
 use std::io::{Read, Seek, Write};
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 static MAPS: Dir = include_dir!("$OUT_DIR/maps.brotli");
 
@@ -54,22 +67,128 @@ pub struct LastGamePlayer {
     pub label: Option<String>,
 }
 
+/// The state of the game in progress
+///
+/// Fields are `None` if the map does not have them or if they are not stored in the NVRAM.
+#[derive(Debug, PartialEq, Default)]
+pub struct GameState {
+    pub credits: Option<i64>,
+    pub max_credits: Option<i64>,
+    pub current_player: Option<i64>,
+    pub current_ball: Option<i64>,
+    pub ball_count: Option<i64>,
+    pub player_count: Option<i64>,
+    pub tilt_warnings: Option<i64>,
+    pub tilted: Option<bool>,
+    pub game_over: Option<bool>,
+    pub extra_balls: Option<i64>,
+    pub free_play: Option<bool>,
+    /// The score of each player, `None` if the score is not stored in the NVRAM
+    pub scores: Vec<Option<i64>>,
+    /// The values for all other keys, list entries have keys like `final_scores.0`
+    pub extras: HashMap<String, NvValue>,
+}
+
+/// The state of each player in the game in progress
+///
+/// Values that are not stored in the NVRAM are [`NvValue::Unavailable`].
+#[derive(Debug, PartialEq, Default)]
+pub struct PlayerState {
+    /// Values that are kept for the player that is up, by key
+    pub current_player: HashMap<String, NvValue>,
+    /// Values for each player, by key
+    pub players: Vec<HashMap<String, NvValue>>,
+}
+
 #[derive(Debug, PartialEq)]
 pub struct DipSwitchInfo {
     pub nr: usize,
     pub name: Option<String>,
 }
 
+/// A setting that was changed by [`Nvram::reset_adjustments_to_defaults`]
+#[derive(Debug, PartialEq)]
+pub struct AdjustmentChange {
+    pub section: String,
+    pub label: Option<String>,
+    /// The value before the reset, `None` if it could not be decoded
+    pub previous: Option<NvValue>,
+    pub value: NvValue,
+}
+
+/// An adjustment whose value is not its `default`, see [`Nvram::changed_adjustments`]
+#[derive(Debug, PartialEq)]
+pub struct AdjustmentDifference {
+    pub section: String,
+    pub label: Option<String>,
+    /// The current value, `None` if it could not be decoded
+    pub value: Option<NvValue>,
+    pub default: NvValue,
+}
+
+/// The adjustments that differ from their defaults, see [`Nvram::changed_adjustments`]
+#[derive(Debug, PartialEq, Default)]
+pub struct ChangedAdjustments {
+    pub changed: Vec<AdjustmentDifference>,
+    /// Section and label of the adjustments that have no `default` to compare with
+    pub without_default: Vec<(String, Option<String>)>,
+    /// Section, label and error of the adjustments with a `default` that can not be stored
+    pub invalid_default: Vec<(String, Option<String>, String)>,
+}
+
+/// A setting made with one or more dip switches, see [`Nvram::dip_settings`]
+#[derive(Debug, PartialEq)]
+pub struct DipSetting {
+    /// The key of the setting in the `dip_switches` of the map
+    pub name: String,
+    pub label: Option<String>,
+    /// The numbers of the switches, 1-based, most significant first
+    pub switches: Vec<usize>,
+    /// The current value, like [`Nvram::get`] returns it
+    pub value: NvValue,
+    /// The values that can be set, by index, empty if the switches are set one by one
+    pub values: Vec<Value>,
+}
+
 /// Main interface to read and write data from a NVRAM file
 pub struct Nvram {
-    pub map: NvramMap,
+    map: NvramMap,
     pub platform: Platform,
     pub nv_path: PathBuf,
+    /// Optional image of the CPU address space, starting at address 0, for example a RAM dump
+    /// of a running PinMAME. Values the map places outside the NVRAM are read from this image.
+    pub memory_image: Option<Vec<u8>>,
+    /// The map as JSON to look up descriptors by path, see [`Nvram::map_value`]
+    map_value: OnceLock<Value>,
 }
 
 impl Nvram {}
 
 impl Nvram {
+    fn new(map: NvramMap, platform: Platform, nv_path: &Path) -> Nvram {
+        Nvram {
+            map,
+            platform,
+            nv_path: nv_path.to_path_buf(),
+            memory_image: None,
+            map_value: OnceLock::new(),
+        }
+    }
+
+    /// The map of the NVRAM
+    pub fn map(&self) -> &NvramMap {
+        &self.map
+    }
+
+    /// The map as JSON, converted on first use
+    fn map_value(&self) -> io::Result<&Value> {
+        if let Some(map) = self.map_value.get() {
+            return Ok(map);
+        }
+        let map = serde_json::to_value(&self.map)?;
+        Ok(self.map_value.get_or_init(|| map))
+    }
+
     /// Open a NVRAM file from the embedded maps
     ///
     /// # Returns
@@ -81,11 +200,7 @@ impl Nvram {
         if let Some(map) = map_opt {
             // find the platform from the map
             let platform = read_platform(&map._metadata.platform)?;
-            Ok(Some(Nvram {
-                map,
-                platform,
-                nv_path: nv_path.to_path_buf(),
-            }))
+            Ok(Some(Nvram::new(map, platform, nv_path)))
         } else {
             Ok(None)
         }
@@ -103,25 +218,40 @@ impl Nvram {
         //let platform = todo!("Determine platform from map");
         if let Some(map) = map_opt {
             let platform = read_platform_local(map.platform())?;
-            Ok(Some(Nvram {
-                map,
-                platform,
-                nv_path: nv_path.to_path_buf(),
-            }))
+            Ok(Some(Nvram::new(map, platform, nv_path)))
         } else {
             Ok(None)
         }
     }
 
+    /// Create a new NVRAM file with the factory defaults, using the embedded maps
+    ///
+    /// The file is sized from the `_ramsize` of the map, or else from the NVRAM regions of
+    /// the platform, followed by the dip switch bytes. Every descriptor `default` is
+    /// written and all checksums are updated. An existing file is never overwritten.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(Nvram))` if the file was created from the map for the ROM
+    /// * `Ok(None)` if no map was found for the ROM, no file is created
+    pub fn create_default(nv_path: &Path) -> io::Result<Option<Nvram>> {
+        let Some(map) = find_map::<NvramMap>(&rom_name(nv_path))? else {
+            return Ok(None);
+        };
+        let platform = read_platform(&map._metadata.platform)?;
+        let nvram = factory_defaults(&map, &platform)?;
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(nv_path)?;
+        file.write_all(&nvram)?;
+        Ok(Some(Nvram::new(map, platform, nv_path)))
+    }
+
     pub fn read_highscores(&mut self) -> io::Result<Vec<HighScore>> {
         let mut file = OpenOptions::new().read(true).open(&self.nv_path)?;
-        read_highscores(
-            self.platform.endian,
-            self.platform.nibble(MemoryLayoutType::NVRam),
-            self.platform.offset(MemoryLayoutType::NVRam),
-            &self.map,
-            &mut file,
-        )
+        let mut memory = Memory::new(&mut file, self.memory_image.as_deref());
+        read_highscores(&mut memory, &self.platform, &self.map)
     }
 
     pub fn clear_highscores(&mut self) -> io::Result<()> {
@@ -130,35 +260,20 @@ impl Nvram {
             .read(true)
             .write(true)
             .open(&self.nv_path)?;
-        clear_highscores(
-            &mut rw_file,
-            self.platform.nibble(MemoryLayoutType::NVRam),
-            self.platform.offset(MemoryLayoutType::NVRam),
-            &self.map,
-        )?;
+        clear_highscores(&mut rw_file, &self.platform, &self.map)?;
         update_all_checksum16(&mut rw_file, &self.map, &self.platform)
     }
 
     pub fn read_mode_champions(&mut self) -> io::Result<Option<Vec<ModeChampion>>> {
         let mut file = OpenOptions::new().read(true).open(&self.nv_path)?;
-        read_mode_champions(
-            &mut file,
-            self.platform.endian,
-            self.platform.nibble(MemoryLayoutType::NVRam),
-            self.platform.offset(MemoryLayoutType::NVRam),
-            &self.map,
-        )
+        let mut memory = Memory::new(&mut file, self.memory_image.as_deref());
+        read_mode_champions(&mut memory, &self.platform, &self.map)
     }
 
     pub fn read_last_game(&mut self) -> io::Result<Option<Vec<LastGamePlayer>>> {
         let mut file = OpenOptions::new().read(true).open(&self.nv_path)?;
-        read_last_game(
-            &mut file,
-            self.platform.endian,
-            self.platform.nibble(MemoryLayoutType::NVRam),
-            self.platform.offset(MemoryLayoutType::NVRam),
-            &self.map,
-        )
+        let mut memory = Memory::new(&mut file, self.memory_image.as_deref());
+        read_last_game(&mut memory, &self.platform, &self.map)
     }
 
     pub fn verify_all_checksum16(&mut self) -> io::Result<Vec<ChecksumMismatch<u16>>> {
@@ -169,24 +284,160 @@ impl Nvram {
     // TODO we probably want to remove this
     pub fn read_replay_score(&mut self) -> io::Result<Option<u64>> {
         let mut file = OpenOptions::new().read(true).open(&self.nv_path)?;
-        read_replay_score(
-            &mut file,
-            self.platform.endian,
-            self.platform.nibble(MemoryLayoutType::NVRam),
-            self.platform.offset(MemoryLayoutType::NVRam),
-            &self.map,
-        )
+        let mut memory = Memory::new(&mut file, self.memory_image.as_deref());
+        read_replay_score(&mut memory, &self.platform, &self.map)
+    }
+
+    /// Read when the last game was played, for games that keep a real-time clock
+    ///
+    /// Returns `None` if the map has no `last_played` or if the clock was never set.
+    pub fn read_last_played(&mut self) -> io::Result<Option<Timestamp>> {
+        let mut file = OpenOptions::new().read(true).open(&self.nv_path)?;
+        let mut memory = Memory::new(&mut file, self.memory_image.as_deref());
+        read_last_played(&mut memory, &self.platform, &self.map)
     }
 
     pub fn read_game_state(&mut self) -> io::Result<Option<HashMap<String, String>>> {
         let mut file = OpenOptions::new().read(true).open(&self.nv_path)?;
-        read_game_state(
-            &mut file,
-            self.platform.endian,
-            self.platform.nibble(MemoryLayoutType::NVRam),
-            self.platform.offset(MemoryLayoutType::NVRam),
+        let mut memory = Memory::new(&mut file, self.memory_image.as_deref());
+        read_game_state(&mut memory, &self.platform, &self.map)
+    }
+
+    pub fn read_typed_game_state(&mut self) -> io::Result<Option<GameState>> {
+        let mut file = OpenOptions::new().read(true).open(&self.nv_path)?;
+        let mut memory = Memory::new(&mut file, self.memory_image.as_deref());
+        read_typed_game_state(&mut memory, &self.platform, &self.map)
+    }
+
+    pub fn read_player_state(&mut self) -> io::Result<Option<PlayerState>> {
+        let mut file = OpenOptions::new().read(true).open(&self.nv_path)?;
+        let mut memory = Memory::new(&mut file, self.memory_image.as_deref());
+        read_player_state(&mut memory, &self.platform, &self.map)
+    }
+
+    /// Read the value of any descriptor in the map
+    ///
+    /// # Arguments
+    /// * `path` - The path of the descriptor in the map, see [`resolve::resolve_selected`] for
+    ///   the syntax, e.g. `game_state/credits` or `adjustments/*/Free Play` without the wildcard
+    pub fn get(&self, path: &str) -> io::Result<NvValue> {
+        let map = self.map_value()?;
+        let descriptor = find_descriptor(map, path)?;
+        let encoding: Encoding = serde_json::from_value(descriptor["encoding"].clone())?;
+        let mut file = OpenOptions::new().read(true).open(&self.nv_path)?;
+        let mut memory = Memory::new(&mut file, self.memory_image.as_deref());
+        Ok(resolve_value(
+            &mut memory,
+            descriptor,
+            encoding,
             &self.map,
-        )
+            &self.platform,
+        )?)
+    }
+
+    /// Write the value of any descriptor in the map and update the checksums
+    ///
+    /// # Arguments
+    /// * `path` - The path of the descriptor in the map, see [`Nvram::get`]
+    /// * `value` - The value to write, an `enum` also accepts one of its values as string
+    pub fn set(&mut self, path: &str, value: NvValue) -> io::Result<()> {
+        let map = self.map_value()?;
+        let descriptor = find_descriptor(map, path)?;
+        let mut rw_file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&self.nv_path)?;
+        write_value(&mut rw_file, descriptor, &value, &self.map, &self.platform)?;
+        update_all_checksum8(&mut rw_file, &self.map, &self.platform)?;
+        update_all_checksum16(&mut rw_file, &self.map, &self.platform)
+    }
+
+    /// Write the `default` of every adjustment and update the checksums
+    ///
+    /// Audits, high scores and adjustments without a `default` are kept. All defaults are
+    /// written to a copy first, the file is only written when every default could be
+    /// stored.
+    ///
+    /// # Arguments
+    /// * `section` - Only reset the adjustments in this section, e.g. `Standard`
+    ///
+    /// # Returns
+    /// The adjustments that changed, in alphabetical order of their section and key
+    pub fn reset_adjustments_to_defaults(
+        &mut self,
+        section: Option<&str>,
+    ) -> io::Result<Vec<AdjustmentChange>> {
+        let map = self.map_value()?;
+        let mut nvram = io::Cursor::new(std::fs::read(&self.nv_path)?);
+        let mut changes = Vec::new();
+        for (section, descriptor) in adjustment_descriptors(map, section)? {
+            let Some(default) = default_value(descriptor) else {
+                continue;
+            };
+            let previous = read_value(&mut nvram, descriptor, &self.map, &self.platform).ok();
+            write_value(&mut nvram, descriptor, &default, &self.map, &self.platform)?;
+            let value = read_value(&mut nvram, descriptor, &self.map, &self.platform)?;
+            if previous.as_ref() != Some(&value) {
+                changes.push(AdjustmentChange {
+                    section: section.to_string(),
+                    label: descriptor_label(descriptor),
+                    previous,
+                    value,
+                });
+            }
+        }
+        update_all_checksum8(&mut nvram, &self.map, &self.platform)?;
+        update_all_checksum16(&mut nvram, &self.map, &self.platform)?;
+        std::fs::write(&self.nv_path, nvram.into_inner())?;
+        Ok(changes)
+    }
+
+    /// Compare every adjustment with its `default`
+    ///
+    /// The defaults are decoded like the current values, e.g. an `enum` default has both
+    /// its index and label. Defaults that can not be stored are reported in
+    /// [`ChangedAdjustments::invalid_default`].
+    pub fn changed_adjustments(&self) -> io::Result<ChangedAdjustments> {
+        let map = self.map_value()?;
+        let nvram = std::fs::read(&self.nv_path)?;
+        let mut current = io::Cursor::new(nvram.clone());
+        // the defaults are written to a copy to decode them
+        let mut defaults = io::Cursor::new(nvram);
+        let mut result = ChangedAdjustments::default();
+        for (section, descriptor) in adjustment_descriptors(map, None)? {
+            let label = descriptor_label(descriptor);
+            let Some(default) = default_value(descriptor) else {
+                result.without_default.push((section.to_string(), label));
+                continue;
+            };
+            let default = write_value(
+                &mut defaults,
+                descriptor,
+                &default,
+                &self.map,
+                &self.platform,
+            )
+            .and_then(|()| read_value(&mut defaults, descriptor, &self.map, &self.platform));
+            let default = match default {
+                Ok(default) => default,
+                Err(e) => {
+                    result
+                        .invalid_default
+                        .push((section.to_string(), label, e.to_string()));
+                    continue;
+                }
+            };
+            let value = read_value(&mut current, descriptor, &self.map, &self.platform).ok();
+            if value.as_ref() != Some(&default) {
+                result.changed.push(AdjustmentDifference {
+                    section: section.to_string(),
+                    label,
+                    value,
+                    default,
+                });
+            }
+        }
+        Ok(result)
     }
 
     pub fn dip_switches_len(&self) -> io::Result<usize> {
@@ -272,6 +523,75 @@ impl Nvram {
         }
     }
 
+    /// List the dip switch settings of the map, ordered by their first switch
+    pub fn dip_settings(&self) -> io::Result<Vec<DipSetting>> {
+        let map = self.map_value()?;
+        let mut file = OpenOptions::new().read(true).open(&self.nv_path)?;
+        let mut settings = Vec::new();
+        let dip_switches = map.get("dip_switches").and_then(Value::as_object);
+        for (name, descriptor) in dip_switches.into_iter().flatten() {
+            let Some(descriptor) = descriptor.as_object() else {
+                continue;
+            };
+            let switches = descriptor
+                .get("offsets")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .map(|offset| serde_json::from_value::<HexOrInteger>(offset.clone()))
+                .map(|offset| offset.map(|offset| u64::from(&offset) as usize))
+                .collect::<Result<Vec<_>, _>>()?;
+            let values = if descriptor.contains_key("values") {
+                descriptor_values(descriptor, &self.map)?
+            } else {
+                Vec::new()
+            };
+            settings.push(DipSetting {
+                name: name.clone(),
+                label: descriptor_label(descriptor),
+                switches,
+                value: read_value(&mut file, descriptor, &self.map, &self.platform)?,
+                values,
+            });
+        }
+        settings.sort_by_key(|setting| setting.switches.first().copied());
+        Ok(settings)
+    }
+
+    /// Set a dip switch setting to one of its values
+    ///
+    /// The index of the value is set on the switches of the setting, most significant first.
+    ///
+    /// # Arguments
+    /// * `name` - The key or label of the setting, see [`DipSetting`]
+    /// * `value` - One of the `values` of the setting
+    pub fn set_dip_setting(&self, name: &str, value: &str) -> io::Result<()> {
+        let map = self.map_value()?;
+        let descriptor = map
+            .get("dip_switches")
+            .and_then(Value::as_object)
+            .and_then(|dip_switches| {
+                dip_switches.get(name).or_else(|| {
+                    dip_switches
+                        .values()
+                        .find(|ds| ds.get("label").and_then(Value::as_str) == Some(name))
+                })
+            })
+            .and_then(Value::as_object)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("No dip switch setting '{name}'"),
+                )
+            })?;
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&self.nv_path)?;
+        let value = NvValue::Text(value.to_string());
+        write_value(&mut file, descriptor, &value, &self.map, &self.platform)
+    }
+
     /// Get the value of a dip switch
     /// # Arguments
     /// * `number` - The number of the dip switch to get, 1-based!
@@ -302,9 +622,59 @@ impl Nvram {
     }
 }
 
-fn open_nvram<T: DeserializeOwned>(nv_path: &Path) -> io::Result<Option<T>> {
-    // get the rom name from the file name
-    let rom_name = nv_path
+/// The adjustment descriptors of the map with their section, optionally of a single section
+fn adjustment_descriptors<'m>(
+    map: &'m Value,
+    section: Option<&str>,
+) -> io::Result<Vec<(&'m str, &'m serde_json::Map<String, Value>)>> {
+    let sections = map.get("adjustments").and_then(Value::as_object);
+    if let Some(section) = section
+        && !sections.is_some_and(|sections| sections.contains_key(section))
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No adjustments section '{section}'"),
+        ));
+    }
+    let mut found = Vec::new();
+    for (name, adjustments) in sections.into_iter().flatten() {
+        if name.starts_with('_') || section.is_some_and(|section| section != name) {
+            continue;
+        }
+        let mut section_descriptors = Vec::new();
+        descriptors(adjustments, name, &mut section_descriptors);
+        found.extend(
+            section_descriptors
+                .into_iter()
+                .map(|(_, descriptor)| (name.as_str(), descriptor)),
+        );
+    }
+    Ok(found)
+}
+
+/// Decode a descriptor from the .nv file, see [`Nvram::get`]
+fn read_value<T: Read + Seek, S: GlobalSettings>(
+    nvram_file: &mut T,
+    descriptor: &serde_json::Map<String, Value>,
+    global_settings: &S,
+    platform: &Platform,
+) -> io::Result<NvValue> {
+    let encoding: Encoding = serde_json::from_value(descriptor["encoding"].clone())?;
+    let mut memory = Memory::new(nvram_file, None);
+    resolve_value(&mut memory, descriptor, encoding, global_settings, platform)
+        .map_err(|warning| io::Error::new(io::ErrorKind::InvalidData, warning.to_string()))
+}
+
+fn descriptor_label(descriptor: &serde_json::Map<String, Value>) -> Option<String> {
+    descriptor
+        .get("label")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+/// The rom name from the file name, `afm_113b` for `afm_113b.nv`
+fn rom_name(nv_path: &Path) -> String {
+    nv_path
         .file_name()
         .unwrap()
         .to_str()
@@ -312,7 +682,11 @@ fn open_nvram<T: DeserializeOwned>(nv_path: &Path) -> io::Result<Option<T>> {
         .split('.')
         .next()
         .unwrap()
-        .to_string();
+        .to_string()
+}
+
+fn open_nvram<T: DeserializeOwned>(nv_path: &Path) -> io::Result<Option<T>> {
+    let rom_name = rom_name(nv_path);
     // check if file exists
     if !nv_path.exists() {
         return Err(io::Error::new(
@@ -324,16 +698,7 @@ fn open_nvram<T: DeserializeOwned>(nv_path: &Path) -> io::Result<Option<T>> {
 }
 
 fn open_nvram_local<T: DeserializeOwned>(nv_path: &Path) -> io::Result<Option<T>> {
-    // get the rom name from the file name
-    let rom_name = nv_path
-        .file_name()
-        .unwrap()
-        .to_str()
-        .unwrap()
-        .split('.')
-        .next()
-        .unwrap()
-        .to_string();
+    let rom_name = rom_name(nv_path);
     // check if file exists
     if !nv_path.exists() {
         return Err(io::Error::new(
@@ -427,41 +792,32 @@ fn read_compressed_json<T: de::DeserializeOwned>(map_file: &File) -> io::Result<
     Ok(data)
 }
 
-fn read_highscores<T: Read + Seek>(
-    endian: Endian,
-    nibble: Nibble,
-    offset: u64,
+fn read_highscores(
+    memory: &mut Memory,
+    platform: &Platform,
     map: &NvramMap,
-    mut nvram_file: &mut T,
 ) -> io::Result<Vec<HighScore>> {
     let scores: Result<Vec<HighScore>, io::Error> = map
         .high_scores
         .iter()
-        .map(|hs| read_highscore(&mut nvram_file, hs, endian, nibble, offset, map))
+        .map(|hs| read_highscore(memory, hs, platform, map))
         .collect();
     scores
 }
 
-fn read_highscore<T: Read + Seek, S: GlobalSettings>(
-    mut nvram_file: &mut T,
+fn read_highscore<S: GlobalSettings>(
+    memory: &mut Memory,
     hs: &model::HighScore,
-    endian: Endian,
-    nibble: Nibble,
-    offset: u64,
+    platform: &Platform,
     global_settings: &S,
 ) -> io::Result<HighScore> {
     let mut initials = "".to_string();
     if let Some(map_initials) = &hs.initials {
-        initials = read_ch_descriptor(
-            &mut nvram_file,
-            map_initials,
-            nibble,
-            offset,
-            global_settings,
-        )?;
+        initials = read_ch_descriptor(memory, map_initials, platform, global_settings)?;
     }
 
-    let score = read_descriptor_to_u64(&mut nvram_file, &hs.score, endian, nibble, offset)?;
+    let resolved = resolve_descriptor(memory, &hs.score, platform, global_settings)?;
+    let score = score_value(&hs.score, resolved)?;
 
     Ok(HighScore {
         label: hs.label.clone(),
@@ -471,59 +827,57 @@ fn read_highscore<T: Read + Seek, S: GlobalSettings>(
     })
 }
 
-fn clear_highscores<T: Write + Seek>(
-    mut nvram_file: &mut T,
-    nibble: Nibble,
-    offset: u64,
+/// The contents of a factory default .nv file, see [`Nvram::create_default`]
+fn factory_defaults(map: &NvramMap, platform: &Platform) -> io::Result<Vec<u8>> {
+    let size = map._ramsize.unwrap_or_else(|| platform.nvram_size()) as usize;
+    let mut nvram = io::Cursor::new(vec![0; size + DIP_SWITCH_BYTES]);
+    let map_value = serde_json::to_value(map)?;
+    let mut found = Vec::new();
+    descriptors(&map_value, "", &mut found);
+    for (path, descriptor) in found {
+        let Some(value) = default_value(descriptor) else {
+            continue;
+        };
+        write_value(&mut nvram, descriptor, &value, map, platform).map_err(|e| {
+            io::Error::new(e.kind(), format!("Can not write default for '{path}': {e}"))
+        })?;
+    }
+    update_all_checksum8(&mut nvram, map, platform)?;
+    update_all_checksum16(&mut nvram, map, platform)?;
+    Ok(nvram.into_inner())
+}
+
+fn clear_highscores<T: Read + Write + Seek>(
+    nvram_file: &mut T,
+    platform: &Platform,
     map: &NvramMap,
 ) -> io::Result<()> {
     for hs in &map.high_scores {
         if let Some(map_initials) = &hs.initials {
-            write_ch(
-                &mut nvram_file,
-                u64::from(
-                    map_initials
-                        .start
-                        .as_ref()
-                        .expect("missing start for ch encoding"),
-                ) - offset,
-                map_initials.length.expect("missing length for ch encoding"),
-                "AAA".to_string(),
-                map.char_map(),
-                &map_initials.nibble.or(Some(nibble)),
-            )?;
-        }
-        if let Some(map_score_start) = &hs.score.start {
-            write_bcd(
-                &mut nvram_file,
-                u64::from(map_score_start) - offset,
-                hs.score.length.unwrap_or(0),
-                hs.score.nibble.unwrap_or(nibble),
-                0,
-            )?;
+            let initials = NvValue::Text("AAA".to_string());
+            write_descriptor(nvram_file, map_initials, &initials, platform, map)?;
         }
+        // the score is cleared to a stored 0, which is displayed as the offset
+        let score = NvValue::Integer(i128::from(hs.score.offset.unwrap_or(0)));
+        write_descriptor(nvram_file, &hs.score, &score, platform, map)?;
     }
     Ok(())
 }
 
-fn read_mode_champion<T: Read + Seek, S: GlobalSettings>(
-    mut nvram_file: &mut T,
+fn read_mode_champion<S: GlobalSettings>(
+    memory: &mut Memory,
     mc: &model::ModeChampion,
-    endian: Endian,
-    nibble: Nibble,
-    offset: u64,
+    platform: &Platform,
     global_settings: &S,
 ) -> io::Result<ModeChampion> {
     let initials = mc
         .initials
         .as_ref()
-        .map(|initials| {
-            read_ch_descriptor(&mut nvram_file, initials, nibble, offset, global_settings)
-        })
+        .map(|initials| read_ch_descriptor(memory, initials, platform, global_settings))
         .transpose()?;
     let score = if let Some(score) = &mc.score {
-        let result = read_descriptor_to_u64(&mut nvram_file, score, endian, nibble, offset)?;
-        Some(result)
+        let resolved = resolve_descriptor(memory, score, platform, global_settings)?;
+        Some(score_value(score, resolved)?)
     } else {
         None
     };
@@ -531,7 +885,13 @@ fn read_mode_champion<T: Read + Seek, S: GlobalSettings>(
     let timestamp = mc
         .timestamp
         .as_ref()
-        .map(|ts| read_descriptor_to_rtc_string(&mut nvram_file, ts))
+        .map(
+            |ts| match resolve_descriptor(memory, ts, platform, global_settings)? {
+                NvValue::Timestamp(timestamp) => Ok(timestamp.to_string()),
+                NvValue::Unavailable => Err(outside_nvram(ts)),
+                other => Err(unexpected_value(ts, &other)),
+            },
+        )
         .transpose()?;
 
     Ok(ModeChampion {
@@ -544,25 +904,23 @@ fn read_mode_champion<T: Read + Seek, S: GlobalSettings>(
     })
 }
 
-fn read_last_game_player<T: Read + Seek>(
-    mut nvram_file: &mut T,
+fn read_last_game_player(
+    memory: &mut Memory,
     descriptor: &Descriptor,
-    endian: Endian,
-    nibble: Nibble,
-    offset: u64,
+    platform: &Platform,
+    map: &NvramMap,
 ) -> io::Result<LastGamePlayer> {
-    let score = read_descriptor_to_u64(&mut nvram_file, descriptor, endian, nibble, offset)?;
+    let resolved = resolve_descriptor(memory, descriptor, platform, map)?;
+    let score = score_value(descriptor, resolved)?;
     Ok(LastGamePlayer {
         score,
         label: descriptor.label.clone(),
     })
 }
 
-fn read_last_game<T: Read + Seek>(
-    mut nvram_file: &mut T,
-    endian: Endian,
-    nibble: Nibble,
-    offset: u64,
+fn read_last_game(
+    memory: &mut Memory,
+    platform: &Platform,
     map: &NvramMap,
 ) -> io::Result<Option<Vec<LastGamePlayer>>> {
     if let Some(lg) = &map.last_game {
@@ -570,7 +928,7 @@ fn read_last_game<T: Read + Seek>(
         // TODO remove once all maps have been updated
         let last_games: Result<Vec<LastGamePlayer>, io::Error> = lg
             .iter()
-            .map(|lg| read_last_game_player(&mut nvram_file, lg, endian, nibble, offset))
+            .map(|lg| read_last_game_player(memory, lg, platform, map))
             .collect();
         Ok(Some(last_games?))
     } else if let Some(game_state) = &map.game_state {
@@ -579,11 +937,11 @@ fn read_last_game<T: Read + Seek>(
             let scores: Result<Vec<LastGamePlayer>, io::Error> = match scores {
                 StateOrStateList::StateList(sl) => sl
                     .iter()
-                    // .filter(|d| match location_for(d, offset) {
+                    // .filter(|d| match location_for(d, platform) {
                     //     Ok(LocateResult::Located(_)) => true,
                     //     _ => false,
                     // })
-                    .map(|d| read_last_game_player(&mut nvram_file, d, endian, nibble, offset))
+                    .map(|d| read_last_game_player(memory, d, platform, map))
                     .collect(),
                 _other => {
                     return Err(io::Error::new(
@@ -597,7 +955,7 @@ fn read_last_game<T: Read + Seek>(
             let scores: Result<Vec<LastGamePlayer>, io::Error> = match scores {
                 StateOrStateList::StateList(sl) => sl
                     .iter()
-                    .map(|d| read_last_game_player(&mut nvram_file, d, endian, nibble, offset))
+                    .map(|d| read_last_game_player(memory, d, platform, map))
                     .collect(),
                 _other => {
                     return Err(io::Error::new(
@@ -615,17 +973,15 @@ fn read_last_game<T: Read + Seek>(
     }
 }
 
-fn read_mode_champions<T: Read + Seek>(
-    mut nvram_file: &mut T,
-    endian: Endian,
-    nibble: Nibble,
-    offset: u64,
+fn read_mode_champions(
+    memory: &mut Memory,
+    platform: &Platform,
     map: &NvramMap,
 ) -> io::Result<Option<Vec<ModeChampion>>> {
     if let Some(mode_champions) = &map.mode_champions {
         let champions: Result<Vec<ModeChampion>, io::Error> = mode_champions
             .iter()
-            .map(|mc| read_mode_champion(&mut nvram_file, mc, endian, nibble, offset, map))
+            .map(|mc| read_mode_champion(memory, mc, platform, map))
             .collect();
         Ok(Some(champions?))
     } else {
@@ -633,283 +989,372 @@ fn read_mode_champions<T: Read + Seek>(
     }
 }
 
-fn read_replay_score<T: Read + Seek>(
-    mut nvram_file: &mut T,
-    endian: Endian,
-    nibble: Nibble,
-    offset: u64,
+fn read_replay_score(
+    memory: &mut Memory,
+    platform: &Platform,
     map: &NvramMap,
 ) -> io::Result<Option<u64>> {
     if let Some(descriptor) = &map.replay_score {
-        let value = read_descriptor_to_u64(&mut nvram_file, descriptor, endian, nibble, offset)?;
-        Ok(Some(value))
+        let resolved = resolve_descriptor(memory, descriptor, platform, map)?;
+        Ok(Some(score_value(descriptor, resolved)?))
     } else {
         Ok(None)
     }
 }
 
-fn read_game_state<T: Read + Seek>(
-    mut nvram_file: &mut T,
-    endian: Endian,
-    nibble: Nibble,
-    offset: u64,
+fn read_last_played(
+    memory: &mut Memory,
+    platform: &Platform,
+    map: &NvramMap,
+) -> io::Result<Option<Timestamp>> {
+    let Some(descriptor) = &map.last_played else {
+        return Ok(None);
+    };
+    match resolve_descriptor(memory, descriptor, platform, map)? {
+        NvValue::Timestamp(timestamp) => Ok(timestamp.is_valid().then_some(timestamp)),
+        NvValue::Unavailable => Err(outside_nvram(descriptor)),
+        other => Err(unexpected_value(descriptor, &other)),
+    }
+}
+
+fn read_game_state(
+    memory: &mut Memory,
+    platform: &Platform,
     map: &NvramMap,
 ) -> io::Result<Option<HashMap<String, String>>> {
     if let Some(game_state) = &map.game_state {
-        // map the hashmap values to a new hashmap with the values read from the nvram file
-        let state: Result<HashMap<String, String>, io::Error> = game_state
-            .iter()
-            .flat_map(|(key, v)| match v {
-                StateOrStateList::State(s) => {
-                    // skip values outside nvram
-                    if let Ok(LocateResult::OutsideNVRAM) = location_for(s, offset) {
-                        return vec![];
-                    }
-                    let r =
-                        read_descriptor_to_string(&mut nvram_file, s, endian, nibble, offset, map)
-                            .map(|r| (key.clone(), r));
-                    vec![r]
-                }
+        let mut state = HashMap::new();
+        for (key, v) in game_state {
+            let descriptors: Vec<(String, &Descriptor)> = match v {
+                StateOrStateList::State(s) => vec![(key.clone(), s)],
                 StateOrStateList::StateList(sl) => sl
                     .iter()
                     .enumerate()
-                    .filter_map(|(index, s)| {
-                        // skip values outside nvram
-                        if let Ok(LocateResult::OutsideNVRAM) = location_for(s, offset) {
-                            return None;
-                        }
-                        Some((index, s))
-                    })
-                    .map(|(index, s)| {
-                        let compund_key = format!("{key}.{index}");
-                        read_descriptor_to_string(&mut nvram_file, s, endian, nibble, offset, map)
-                            .map(|r| (compund_key, r))
-                    })
+                    .map(|(index, s)| (format!("{key}.{index}"), s))
                     .collect(),
-                StateOrStateList::Notes(_) => {
-                    vec![]
-                }
-            })
-            .collect();
-
-        Ok(Some(state?))
+                StateOrStateList::Notes(_) => vec![],
+            };
+            for (key, descriptor) in descriptors {
+                let value = match resolve_descriptor(memory, descriptor, platform, map) {
+                    // skip values that are not available
+                    Ok(NvValue::Unavailable) => continue,
+                    Ok(value) => value.to_string(),
+                    Err(e) if e.kind() == io::ErrorKind::Unsupported => e.to_string(),
+                    Err(e) => return Err(e),
+                };
+                state.insert(key, value);
+            }
+        }
+        Ok(Some(state))
     } else {
         Ok(None)
     }
 }
 
-fn read_descriptor_to_string<T: Read + Seek, S: GlobalSettings>(
-    mut nvram_file: &mut T,
-    descriptor: &Descriptor,
-    endian: Endian,
-    nibble: Nibble,
-    offset: u64,
-    global_settings: &S,
-) -> io::Result<String> {
-    match descriptor.encoding {
-        Encoding::Ch => {
-            read_ch_descriptor(&mut nvram_file, descriptor, nibble, offset, global_settings)
-        }
-        Encoding::Int => {
-            let location = match location_for(descriptor, offset)? {
-                LocateResult::OutsideNVRAM => {
-                    return Ok("Value is stored outside the NVRAM".to_string());
+fn read_typed_game_state(
+    memory: &mut Memory,
+    platform: &Platform,
+    map: &NvramMap,
+) -> io::Result<Option<GameState>> {
+    let Some(game_state) = &map.game_state else {
+        return Ok(None);
+    };
+    let mut state = GameState::default();
+    for (key, v) in game_state {
+        match v {
+            StateOrStateList::State(descriptor) => {
+                let Some(value) = read_state_value(memory, descriptor, platform, map)? else {
+                    continue;
+                };
+                let number = state_integer(&value)?;
+                let flag = state_flag(&value);
+                match key.as_str() {
+                    "credits" => state.credits = number,
+                    "max_credits" => state.max_credits = number,
+                    "current_player" => state.current_player = number,
+                    "current_ball" => state.current_ball = number,
+                    "ball_count" => state.ball_count = number,
+                    "player_count" => state.player_count = number,
+                    "tilt_warnings" => state.tilt_warnings = number,
+                    "tilted" => state.tilted = flag,
+                    "game_over" => state.game_over = flag,
+                    "extra_balls" => state.extra_balls = number,
+                    "free_play" => state.free_play = flag,
+                    _ => {
+                        state.extras.insert(key.clone(), value);
+                    }
                 }
-                LocateResult::Located(loc) => loc,
-            };
-            let score = read_int(
-                &mut nvram_file,
-                endian,
-                nibble,
-                location,
-                descriptor
-                    .scale
-                    .as_ref()
-                    .unwrap_or(&Number::from(DEFAULT_SCALE)),
-            )?;
-            Ok(score.to_string())
-        }
-        Encoding::Bcd => {
-            let location = match location_for(descriptor, offset)? {
-                LocateResult::OutsideNVRAM => {
-                    return Ok("Value is stored outside the NVRAM".to_string());
+            }
+            StateOrStateList::StateList(sl) if key == "scores" => {
+                for descriptor in sl {
+                    let score = match read_state_value(memory, descriptor, platform, map)? {
+                        Some(value) => state_integer(&value)?,
+                        None => None,
+                    };
+                    state.scores.push(score);
                 }
-                LocateResult::Located(loc) => loc,
-            };
-            let score = read_bcd(
-                &mut nvram_file,
-                location,
-                descriptor.nibble.unwrap_or(nibble),
-                descriptor
-                    .scale
-                    .as_ref()
-                    .unwrap_or(&Number::from(DEFAULT_SCALE)),
-                endian,
-            )?;
-            Ok(score.to_string())
-        }
-        Encoding::Bits => Ok("Bits encoding not implemented".to_string()),
-        Encoding::Bool => {
-            let location = match location_for(descriptor, offset)? {
-                LocateResult::OutsideNVRAM => {
-                    return Ok("Value is stored outside the NVRAM".to_string());
+            }
+            StateOrStateList::StateList(sl) => {
+                for (index, descriptor) in sl.iter().enumerate() {
+                    if let Some(value) = read_state_value(memory, descriptor, platform, map)? {
+                        state.extras.insert(format!("{key}.{index}"), value);
+                    }
                 }
-                LocateResult::Located(loc) => loc,
-            };
-            let bool = read_bool(
-                nvram_file,
-                nibble,
-                endian,
-                location,
-                descriptor.invert.unwrap_or(DEFAULT_INVERT),
-            )?;
-            Ok(bool.to_string())
+            }
+            StateOrStateList::Notes(_) => {}
         }
-        Encoding::Dipsw => Ok("Dipsw encoding not implemented".to_string()),
-        other => todo!("Encoding not implemented: {:?}", other),
     }
+    Ok(Some(state))
 }
 
-fn read_descriptor_to_u64<T: Read + Seek>(
-    mut nvram_file: &mut T,
-    descriptor: &Descriptor,
-    endian: Endian,
-    nibble: Nibble,
-    offset: u64,
-) -> io::Result<u64> {
-    match descriptor.encoding {
-        Encoding::Bcd => {
-            let location = match location_for(descriptor, offset)? {
-                LocateResult::OutsideNVRAM => {
-                    return Err(io::Error::new(
-                        io::ErrorKind::InvalidData,
-                        format!(
-                            "Descriptor '{}' points outside NVRAM",
-                            descriptor.label.as_deref().unwrap_or("unknown")
-                        ),
-                    ));
+fn read_player_state(
+    memory: &mut Memory,
+    platform: &Platform,
+    map: &NvramMap,
+) -> io::Result<Option<PlayerState>> {
+    let Some(player_state) = &map.player_state else {
+        return Ok(None);
+    };
+    let mut state = PlayerState::default();
+    for (key, v) in player_state {
+        match v {
+            StateOrStateList::State(descriptor) => {
+                if let Some(value) = read_player_value(memory, descriptor, platform, map)? {
+                    state.current_player.insert(key.clone(), value);
                 }
-                LocateResult::Located(loc) => loc,
-            };
-            read_bcd(
-                &mut nvram_file,
-                location,
-                descriptor.nibble.unwrap_or(nibble),
-                descriptor
-                    .scale
-                    .as_ref()
-                    .unwrap_or(&Number::from(DEFAULT_SCALE)),
-                endian,
-            )
-        }
-        Encoding::Int => {
-            let location = match location_for(descriptor, offset)? {
-                LocateResult::OutsideNVRAM => {
-                    return Err(io::Error::new(
-                        io::ErrorKind::InvalidData,
-                        format!(
-                            "Descriptor '{}' points outside NVRAM",
-                            descriptor.label.as_deref().unwrap_or("unknown")
-                        ),
-                    ));
+            }
+            StateOrStateList::StateList(sl) => {
+                if state.players.len() < sl.len() {
+                    state.players.resize_with(sl.len(), HashMap::new);
                 }
-                LocateResult::Located(loc) => loc,
-            };
-            read_int(
-                &mut nvram_file,
-                endian,
-                descriptor.nibble.unwrap_or(nibble),
-                location,
-                descriptor
-                    .scale
-                    .as_ref()
-                    .unwrap_or(&Number::from(DEFAULT_SCALE)),
-            )
+                for (player, descriptor) in state.players.iter_mut().zip(sl) {
+                    if let Some(value) = read_player_value(memory, descriptor, platform, map)? {
+                        player.insert(key.clone(), value);
+                    }
+                }
+            }
+            StateOrStateList::Notes(_) => {}
         }
-        other => todo!("Encoding not implemented: {:?}", other),
     }
+    Ok(Some(state))
 }
 
-fn read_descriptor_to_rtc_string<T: Read + Seek>(
-    mut nvram_file: &mut T,
-    ts: &Descriptor,
-) -> io::Result<String> {
-    match &ts.encoding {
-        Encoding::WpcRtc => read_wpc_rtc(
-            &mut nvram_file,
-            ts.start
-                .as_ref()
-                .expect("missing start for wpc_rtc encoding")
-                .into(),
-            ts.length.expect("missing length for wpc_rtc encoding"),
-        ),
-        other => todo!("Timestamp encoding not implemented: {:?}", other),
+/// Read a player state value, `None` if the encoding is not supported
+fn read_player_value(
+    memory: &mut Memory,
+    descriptor: &Descriptor,
+    platform: &Platform,
+    map: &NvramMap,
+) -> io::Result<Option<NvValue>> {
+    match resolve_descriptor(memory, descriptor, platform, map) {
+        Ok(value) => Ok(Some(value)),
+        Err(e) if e.kind() == io::ErrorKind::Unsupported => Ok(None),
+        Err(e) => Err(e),
     }
 }
 
-enum LocateResult {
-    OutsideNVRAM,
-    Located(Location),
+/// Read a game state value, `None` if it is not available or the encoding is not supported
+fn read_state_value(
+    memory: &mut Memory,
+    descriptor: &Descriptor,
+    platform: &Platform,
+    map: &NvramMap,
+) -> io::Result<Option<NvValue>> {
+    match resolve_descriptor(memory, descriptor, platform, map) {
+        Ok(NvValue::Unavailable) => Ok(None),
+        Ok(value) => Ok(Some(value)),
+        Err(e) if e.kind() == io::ErrorKind::Unsupported => Ok(None),
+        Err(e) => Err(e),
+    }
 }
 
-/// Read a `ch` (character) descriptor, supporting both `start` and `offsets`.
-fn read_ch_descriptor<T: Read + Seek, S: GlobalSettings>(
+/// A game state number, the index for an `enum`
+fn state_integer(value: &NvValue) -> io::Result<Option<i64>> {
+    let number = match value {
+        NvValue::Integer(number) => *number,
+        NvValue::Enum { index, .. } => *index as i128,
+        _ => return Ok(None),
+    };
+    i64::try_from(number)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn state_flag(value: &NvValue) -> Option<bool> {
+    match value {
+        NvValue::Bool(flag) => Some(*flag),
+        NvValue::Integer(number) => Some(*number != 0),
+        _ => None,
+    }
+}
+
+/// Decode the value of any descriptor with [`resolve_value`], like [`Nvram::get`] does
+///
+/// Values that are not stored in the NVRAM, and are not in the memory image, are
+/// [`NvValue::Unavailable`].
+fn resolve_descriptor<S: GlobalSettings>(
+    memory: &mut Memory,
+    descriptor: &Descriptor,
+    platform: &Platform,
+    global_settings: &S,
+) -> io::Result<NvValue> {
+    let Value::Object(descriptor_map) = serde_json::to_value(descriptor)? else {
+        unreachable!("a descriptor serializes to an object");
+    };
+    match resolve_value(
+        memory,
+        &descriptor_map,
+        descriptor.encoding,
+        global_settings,
+        platform,
+    ) {
+        Ok(value) => Ok(value),
+        Err(ResolveWarning::OutsideNvram) => Ok(NvValue::Unavailable),
+        Err(warning) => Err(warning.into()),
+    }
+}
+
+/// Write the value of a descriptor with [`write_value`], the counterpart of
+/// [`resolve_descriptor`]
+fn write_descriptor<T: Read + Write + Seek, S: GlobalSettings>(
     nvram_file: &mut T,
     descriptor: &Descriptor,
-    nibble: Nibble,
-    offset: u64,
+    value: &NvValue,
+    platform: &Platform,
     global_settings: &S,
-) -> io::Result<String> {
-    let location = match location_for(descriptor, offset)? {
-        LocateResult::OutsideNVRAM => {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "Value is stored outside the NVRAM",
-            ));
-        }
-        LocateResult::Located(loc) => loc,
+) -> io::Result<()> {
+    if !matches!(
+        location_for(descriptor, platform)?,
+        LocateResult::Located(_)
+    ) {
+        return Err(outside_nvram(descriptor));
+    }
+    let Value::Object(descriptor_map) = serde_json::to_value(descriptor)? else {
+        unreachable!("a descriptor serializes to an object");
     };
-    read_ch(
+    write_value(
         nvram_file,
+        &descriptor_map,
+        value,
+        global_settings,
+        platform,
+    )
+}
+
+/// The number of a resolved score, mapping a `special_values` label back to its number
+fn score_value(descriptor: &Descriptor, value: NvValue) -> io::Result<u64> {
+    let number = match value {
+        NvValue::Integer(number) => number,
+        NvValue::Text(label) => {
+            let key = descriptor
+                .special_values
+                .iter()
+                .flatten()
+                .find(|(_, special)| **special == label)
+                .map(|(key, _)| key);
+            match key {
+                Some(key) => key
+                    .parse()
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+                None => return Err(unexpected_value(descriptor, &NvValue::Text(label))),
+            }
+        }
+        NvValue::Unavailable => return Err(outside_nvram(descriptor)),
+        other => return Err(unexpected_value(descriptor, &other)),
+    };
+    u64::try_from(number).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn outside_nvram(descriptor: &Descriptor) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!(
+            "Descriptor '{}' points outside NVRAM",
+            descriptor.label.as_deref().unwrap_or("unknown")
+        ),
+    )
+}
+
+fn unexpected_value(descriptor: &Descriptor, value: &NvValue) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!(
+            "Unexpected value for descriptor '{}': {:?}",
+            descriptor.label.as_deref().unwrap_or("unknown"),
+            value
+        ),
+    )
+}
+
+/// Read a `ch` (character) descriptor, supporting both `start` and `offsets`.
+fn read_ch_descriptor<S: GlobalSettings>(
+    memory: &mut Memory,
+    descriptor: &Descriptor,
+    platform: &Platform,
+    global_settings: &S,
+) -> io::Result<String> {
+    let Some((mut stream, location)) = memory.stream(location_for(descriptor, platform)?) else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Value is stored outside the NVRAM",
+        ));
+    };
+    let period_flag = descriptor
+        .period_flag
+        .or(platform.period_flag)
+        .unwrap_or(false);
+    let text = read_ch(
+        &mut stream,
         location,
         descriptor.mask.as_ref().map(|m| m.into()),
-        global_settings.char_map(),
-        descriptor.nibble.unwrap_or(nibble),
+        &Charset::new(global_settings.char_map(), period_flag),
+        descriptor_nibble(descriptor, platform),
         descriptor.null,
-    )
+    )?;
+    Ok(text.to_string())
 }
 
-fn location_for(descriptor: &Descriptor, offset: u64) -> io::Result<LocateResult> {
+/// The first CPU address a descriptor refers to
+fn first_address(descriptor: &Descriptor) -> Option<u64> {
     match &descriptor.offsets {
+        Some(offsets) => offsets.first().map(u64::from),
+        None => descriptor.start.as_ref().map(u64::from),
+    }
+}
+
+/// The nibble of the memory region the descriptor lives in
+fn platform_nibble(descriptor: &Descriptor, platform: &Platform) -> Nibble {
+    first_address(descriptor)
+        .map(|address| platform.nibble(address))
+        .unwrap_or(Nibble::Both)
+}
+
+/// The descriptor's own nibble, falling back to the one of the memory region it lives in
+fn descriptor_nibble(descriptor: &Descriptor, platform: &Platform) -> Nibble {
+    descriptor
+        .nibble
+        .unwrap_or_else(|| platform_nibble(descriptor, platform))
+}
+
+fn location_for(descriptor: &Descriptor, platform: &Platform) -> io::Result<LocateResult> {
+    let location = match &descriptor.offsets {
         None => match &descriptor.start {
-            Some(start) => {
-                let start = u64::from(start);
-                if start < offset {
-                    return Ok(LocateResult::OutsideNVRAM);
-                }
-                Ok(LocateResult::Located(Location::Continuous {
-                    start: start - offset,
-                    length: descriptor.length.unwrap_or(DEFAULT_LENGTH),
-                }))
+            Some(start) => Location::Continuous {
+                start: u64::from(start),
+                length: descriptor.length.unwrap_or(DEFAULT_LENGTH),
+            },
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Descriptor without offsets requires start",
+                ));
             }
-            _ => Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "Descriptor without offsets requires start",
-            )),
         },
-        Some(offsets) => {
-            // check if any offset is outside nvram
-            for o in offsets {
-                let o_u64 = u64::from(o);
-                if o_u64 < offset {
-                    return Ok(LocateResult::OutsideNVRAM);
-                }
-            }
-            Ok(LocateResult::Located(Location::Scattered {
-                offsets: offsets.iter().map(|o| u64::from(o) - offset).collect(),
-            }))
-        }
-    }
+        Some(offsets) => Location::Scattered {
+            offsets: offsets.iter().map(u64::from).collect(),
+        },
+    };
+    locate(platform, location)
 }
 
 #[cfg(test)]
@@ -939,6 +1384,708 @@ mod tests {
         Ok(())
     }
 
+    fn test_nvram(dir: &Path, nvram: &[u8]) -> io::Result<Nvram> {
+        test_util::nvram_with_map(
+            dir,
+            nvram,
+            serde_json::json!({
+                "adjustments": {
+                    "Standard": {
+                        "01": {"label": "Replay System", "encoding": "enum", "start": 0, "values": ["Fixed", "Auto %"]},
+                        "02": {"label": "Free Play", "encoding": "bool", "start": 1}
+                    }
+                },
+                "game_state": {
+                    "credits": {"label": "Credits", "encoding": "int", "start": 2, "length": 2, "scale": 10}
+                },
+                "checksum16": [{"start": 0, "end": 5, "label": "Adjustments"}]
+            }),
+        )
+    }
+
+    #[test]
+    fn test_get_set() -> io::Result<()> {
+        let dir = testdir!();
+        let mut nvram = test_nvram(&dir, &[0x00, 0x00, 0x00, 0x05, 0xFF, 0xFA])?;
+        assert_eq!(NvValue::Integer(50), nvram.get("game_state.credits")?);
+        assert_eq!(
+            NvValue::Bool(false),
+            nvram.get("adjustments/Standard/Free Play")?
+        );
+
+        nvram.set("game_state.credits", NvValue::Integer(1230))?;
+        nvram.set(
+            "adjustments/Standard/Replay System",
+            NvValue::Text("Auto %".to_string()),
+        )?;
+        nvram.set("adjustments/Standard/Free Play", NvValue::Bool(true))?;
+
+        assert_eq!(NvValue::Integer(1230), nvram.get("game_state.credits")?);
+        assert!(matches!(
+            nvram.get("adjustments/Standard/Replay System")?,
+            NvValue::Enum { index: 1, .. }
+        ));
+        assert_eq!(
+            std::fs::read(&nvram.nv_path)?,
+            vec![0x01, 0x01, 0x00, 0x7B, 0xFF, 0x82]
+        );
+        assert_eq!(
+            Vec::<ChecksumMismatch<u16>>::new(),
+            nvram.verify_all_checksum16()?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_invalid() -> io::Result<()> {
+        let dir = testdir!();
+        let mut nvram = test_nvram(&dir, &[0x00; 6])?;
+        let result = nvram.set("game_state.credits", NvValue::Integer(1234));
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Value 1234 can not be stored with scale 10"
+        );
+        let result = nvram.set("game_state.unknown", NvValue::Integer(1));
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "No descriptor found for path 'game_state.unknown'"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_clear_highscores() -> io::Result<()> {
+        let dir = testdir!();
+        let mut nvram = test_util::nvram_with_map(
+            &dir,
+            &[0x41, 0x42, 0x43, 0x12, 0xFF, 0x34],
+            serde_json::json!({
+                "high_scores": [{
+                    "label": "Grand Champion",
+                    "initials": {"encoding": "ch", "start": 0, "length": 3},
+                    "score": {"encoding": "bcd", "offsets": [3, 5]}
+                }]
+            }),
+        )?;
+        nvram.clear_highscores()?;
+        assert_eq!(
+            std::fs::read(&nvram.nv_path)?,
+            vec![0x41, 0x41, 0x41, 0x00, 0xFF, 0x00]
+        );
+
+        let mut nvram = test_util::nvram_with_map(
+            &dir,
+            &[0x00, 0x12, 0x34, 0x00, 0x00, 0x00],
+            serde_json::json!({
+                "high_scores": [{
+                    "label": "Grand Champion",
+                    "score": {"encoding": "bcd", "start": 1, "length": 2, "min": 100}
+                }]
+            }),
+        )?;
+        let result = nvram.clear_highscores();
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Value out of range: 100 ≤ 0"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_undecoded_text() -> io::Result<()> {
+        let dir = testdir!();
+        let mut nvram = test_util::nvram_with_map(
+            &dir,
+            &[0x41, 0xFF, 0x43, 0xC4, 0x00, 0x00],
+            serde_json::json!({
+                "game_state": {
+                    "initials": {"label": "Initials", "encoding": "ch", "start": 0, "length": 3},
+                    "message": {"label": "Message", "encoding": "ch", "start": 3, "length": 1, "period_flag": true}
+                }
+            }),
+        )?;
+        let initials = nvram.get("game_state/initials")?;
+        assert_eq!(
+            NvValue::UndecodedText {
+                text: "A\u{FFFD}C".to_string(),
+                raw: vec![0x41, 0xFF, 0x43],
+            },
+            initials
+        );
+        let result = nvram.set("game_state/initials", NvValue::Text(initials.to_string()));
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Text with undecoded characters can not be written, write its raw bytes instead"
+        );
+        nvram.set("game_state/initials", NvValue::Text("ABC".to_string()))?;
+        nvram.set("game_state/initials", initials)?;
+        assert_eq!(
+            std::fs::read(&nvram.nv_path)?,
+            vec![0x41, 0xFF, 0x43, 0xC4, 0x00, 0x00]
+        );
+
+        assert_eq!(
+            NvValue::Text("D.".to_string()),
+            nvram.get("game_state/message")?
+        );
+        nvram.set("game_state/message", NvValue::Text("E.".to_string()))?;
+        assert_eq!(Some(0xC5), std::fs::read(&nvram.nv_path)?.get(3).copied());
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_highscores_special_values() -> io::Result<()> {
+        let dir = testdir!();
+        let mut nvram = test_util::nvram_with_map(
+            &dir,
+            &[0x00, 0x00, 0x12, 0x34, 0x00, 0x00],
+            serde_json::json!({
+                "high_scores": [
+                    {
+                        "label": "Grand Champion",
+                        "score": {"encoding": "bcd", "start": 0, "length": 2, "special_values": {"0": "NONE"}}
+                    },
+                    {
+                        "label": "High Score #1",
+                        "score": {"encoding": "bcd", "start": 2, "length": 2, "special_values": {"0": "NONE"}}
+                    }
+                ]
+            }),
+        )?;
+        let scores: Vec<u64> = nvram.read_highscores()?.iter().map(|hs| hs.score).collect();
+        assert_eq!(vec![0, 1234], scores);
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_bits_unsupported() -> io::Result<()> {
+        let dir = testdir!();
+        let nvram = test_util::nvram_with_map(
+            &dir,
+            &[0x00; 6],
+            serde_json::json!({
+                "game_state": {
+                    "bonus": {"label": "Bonus", "encoding": "bits", "start": 0, "length": 2}
+                }
+            }),
+        )?;
+        let error = nvram.get("game_state/bonus").unwrap_err();
+        assert_eq!(io::ErrorKind::Unsupported, error.kind());
+        assert_eq!("Reading bits values is not supported", error.to_string());
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_typed_game_state() -> io::Result<()> {
+        let dir = testdir!();
+        let mut nvram = test_util::nvram_with_map(
+            &dir,
+            &[0x03, 0x01, 0x00, 0x12, 0x34, 0x07],
+            serde_json::json!({
+                "game_state": {
+                    "credits": {"label": "Credits", "encoding": "int", "start": 0},
+                    "current_player": {"label": "Current Player", "encoding": "int", "start": 1, "offset": 1},
+                    "tilted": {"label": "Tilted", "encoding": "bool", "start": 2},
+                    "scores": [
+                        {"label": "Player 1", "encoding": "bcd", "start": 3, "length": 2},
+                        {"label": "Player 2", "encoding": "bcd", "start": "0x100", "length": 2}
+                    ],
+                    "bonus": {"label": "Bonus", "encoding": "int", "start": 5, "offset": 1},
+                    "ball_save": {"label": "Ball Save", "encoding": "int", "start": 2, "special_values": {"0": "OFF"}}
+                }
+            }),
+        )?;
+        let expected = GameState {
+            credits: Some(3),
+            current_player: Some(2),
+            tilted: Some(false),
+            scores: vec![Some(1234), None],
+            extras: HashMap::from([
+                ("bonus".to_string(), NvValue::Integer(8)),
+                ("ball_save".to_string(), NvValue::Text("OFF".to_string())),
+            ]),
+            ..GameState::default()
+        };
+        let game_state = nvram.read_typed_game_state()?.unwrap();
+        assert_eq!(nvram.get("game_state/bonus")?, game_state.extras["bonus"]);
+        assert_eq!(
+            nvram.get("game_state/ball_save")?,
+            game_state.extras["ball_save"]
+        );
+        assert_eq!(expected, game_state);
+        let legacy = nvram.read_game_state()?.unwrap();
+        assert_eq!("8", legacy["bonus"]);
+        assert_eq!("OFF", legacy["ball_save"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_typed_game_state_exact() -> io::Result<()> {
+        let dir = testdir!();
+        let mut nvram = test_util::nvram_with_map(
+            &dir,
+            &[0x99, 0x87, 0x65, 0x43, 0x21, 0x00],
+            serde_json::json!({
+                "game_state": {
+                    "tilt_warnings": {"label": "Tilt Warnings", "encoding": "int", "start": 5, "offset": -1},
+                    "scores": [
+                        {"label": "Player 1", "encoding": "bcd", "start": 0, "length": 5, "scale": 1000}
+                    ]
+                }
+            }),
+        )?;
+        let expected = GameState {
+            tilt_warnings: Some(-1),
+            scores: vec![Some(9_987_654_321_000)],
+            ..GameState::default()
+        };
+        assert_eq!(Some(expected), nvram.read_typed_game_state()?);
+        assert_eq!(NvValue::Integer(-1), nvram.get("game_state/tilt_warnings")?);
+        assert_eq!(
+            NvValue::Integer(9_987_654_321_000),
+            nvram.get("game_state/scores/0")?
+        );
+
+        nvram.set("game_state/tilt_warnings", NvValue::Integer(2))?;
+        assert_eq!(Some(3), std::fs::read(&nvram.nv_path)?.get(5).copied());
+        let result = nvram.set("game_state/tilt_warnings", NvValue::Integer(-2));
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Value -2 can not be stored with scale 1"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_reset_adjustments_to_defaults() -> io::Result<()> {
+        let dir = testdir!();
+        let mut nvram = test_util::nvram_with_map(
+            &dir,
+            &[0x05, 0x00, 0x02, 0x07, 0x00, 0x00],
+            serde_json::json!({
+                "adjustments": {
+                    "Standard": {
+                        "01": {"label": "Balls Per Game", "encoding": "int", "start": 0, "default": 3},
+                        "02": {"label": "Free Play", "encoding": "enum", "start": 1, "values": ["No", "Yes"], "default": "No"},
+                        "03": {"label": "Replay Level", "encoding": "int", "start": 3}
+                    },
+                    "Feature": {
+                        "01": {"label": "Extra Ball", "encoding": "enum", "start": 2, "values": ["Off", "On"], "default": "On"}
+                    }
+                },
+                "checksum8": [{"start": 0, "end": 5, "label": "Adjustments"}]
+            }),
+        )?;
+        let changes = nvram.reset_adjustments_to_defaults(Some("Standard"))?;
+        let balls = AdjustmentChange {
+            section: "Standard".to_string(),
+            label: Some("Balls Per Game".to_string()),
+            previous: Some(NvValue::Integer(5)),
+            value: NvValue::Integer(3),
+        };
+        assert_eq!(vec![balls], changes);
+        assert_eq!(
+            vec![0x03, 0x00, 0x02, 0x07, 0x00, 0xF3],
+            std::fs::read(&nvram.nv_path)?
+        );
+
+        let changes = nvram.reset_adjustments_to_defaults(None)?;
+        let extra_ball = AdjustmentChange {
+            section: "Feature".to_string(),
+            label: Some("Extra Ball".to_string()),
+            previous: None,
+            value: NvValue::Enum {
+                index: 1,
+                label: Some(Value::from("On")),
+            },
+        };
+        assert_eq!(vec![extra_ball], changes);
+        assert_eq!(
+            vec![0x03, 0x00, 0x01, 0x07, 0x00, 0xF4],
+            std::fs::read(&nvram.nv_path)?
+        );
+
+        let result = nvram.reset_adjustments_to_defaults(Some("Pricing"));
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "No adjustments section 'Pricing'"
+        );
+
+        // nothing is written when one of the defaults can not be stored
+        let mut nvram = test_util::nvram_with_map(
+            &dir,
+            &[0x05, 0x00, 0x02, 0x07, 0x00, 0x00],
+            serde_json::json!({
+                "adjustments": {
+                    "Standard": {
+                        "01": {"label": "Balls Per Game", "encoding": "int", "start": 0, "default": 3},
+                        "02": {"label": "Replay Level", "encoding": "int", "start": 3, "default": 300}
+                    }
+                },
+                "checksum8": [{"start": 0, "end": 5, "label": "Adjustments"}]
+            }),
+        )?;
+        let result = nvram.reset_adjustments_to_defaults(None);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Value 300 does not fit in 1 bytes"
+        );
+        assert_eq!(
+            vec![0x05, 0x00, 0x02, 0x07, 0x00, 0x00],
+            std::fs::read(&nvram.nv_path)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_changed_adjustments() -> io::Result<()> {
+        let dir = testdir!();
+        let nvram = test_util::nvram_with_map(
+            &dir,
+            &[0x05, 0x00, 0x02, 0x07, 0x80, 0x00],
+            serde_json::json!({
+                "adjustments": {
+                    "Standard": {
+                        "01": {"label": "Balls Per Game", "encoding": "int", "start": 0, "default": 3},
+                        "02": {"label": "Free Play", "encoding": "enum", "start": 1, "values": ["No", "Yes"], "default": "No"},
+                        "03": {"label": "Replay Level", "encoding": "int", "start": 3},
+                        "04": {"label": "Tournament", "encoding": "bool", "start": 4, "mask": "0x80", "default": 0}
+                    },
+                    "Feature": [
+                        {"label": "Extra Ball", "encoding": "enum", "start": 2, "values": ["Off", "On"], "default": "On"},
+                        {"label": "Special", "encoding": "enum", "start": 5, "values": ["Off", "On"], "default": "Award"}
+                    ]
+                }
+            }),
+        )?;
+        let expected = ChangedAdjustments {
+            changed: vec![
+                AdjustmentDifference {
+                    section: "Feature".to_string(),
+                    label: Some("Extra Ball".to_string()),
+                    value: None,
+                    default: NvValue::Enum {
+                        index: 1,
+                        label: Some(Value::from("On")),
+                    },
+                },
+                AdjustmentDifference {
+                    section: "Standard".to_string(),
+                    label: Some("Balls Per Game".to_string()),
+                    value: Some(NvValue::Integer(5)),
+                    default: NvValue::Integer(3),
+                },
+                AdjustmentDifference {
+                    section: "Standard".to_string(),
+                    label: Some("Tournament".to_string()),
+                    value: Some(NvValue::Bool(true)),
+                    default: NvValue::Bool(false),
+                },
+            ],
+            without_default: vec![("Standard".to_string(), Some("Replay Level".to_string()))],
+            invalid_default: vec![(
+                "Feature".to_string(),
+                Some("Special".to_string()),
+                "Can not write 'Award' as enum".to_string(),
+            )],
+        };
+        assert_eq!(expected, nvram.changed_adjustments()?);
+        // nothing is written to the file
+        assert_eq!(
+            vec![0x05, 0x00, 0x02, 0x07, 0x80, 0x00],
+            std::fs::read(&nvram.nv_path)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_dip_settings() -> io::Result<()> {
+        let dir = testdir!();
+        let nvram = test_util::nvram_with_map(
+            &dir,
+            &[
+                0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x00, 0x00,
+            ],
+            serde_json::json!({
+                "dip_switches": {
+                    "test": {"label": "Test Mode", "encoding": "dipsw", "offsets": [9]},
+                    "coin": {
+                        "label": "Left Coin Chute", "encoding": "dipsw", "offsets": [1, 2, 3],
+                        "values": ["1/1", "1/2", "1/3", "1/4", "2/1", "2/3", "3/1", "3/2"]
+                    }
+                }
+            }),
+        )?;
+        let coin_values: Vec<Value> = ["1/1", "1/2", "1/3", "1/4", "2/1", "2/3", "3/1", "3/2"]
+            .into_iter()
+            .map(Value::from)
+            .collect();
+        let expected = vec![
+            DipSetting {
+                name: "coin".to_string(),
+                label: Some("Left Coin Chute".to_string()),
+                switches: vec![1, 2, 3],
+                value: NvValue::Enum {
+                    index: 5,
+                    label: Some(Value::from("2/3")),
+                },
+                values: coin_values,
+            },
+            DipSetting {
+                name: "test".to_string(),
+                label: Some("Test Mode".to_string()),
+                switches: vec![9],
+                value: NvValue::Flags(vec![false]),
+                values: vec![],
+            },
+        ];
+        assert_eq!(expected, nvram.dip_settings()?);
+
+        nvram.set_dip_setting("Left Coin Chute", "1/2")?;
+        assert_eq!(Some(0x04), std::fs::read(&nvram.nv_path)?.get(6).copied());
+        nvram.set_dip_setting("coin", "3/2")?;
+        assert_eq!(Some(0x07), std::fs::read(&nvram.nv_path)?.get(6).copied());
+        assert!(nvram.get_dip_switch(1)?);
+
+        let result = nvram.set_dip_setting("coin", "4/1");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Can not write '4/1' as dipsw"
+        );
+        let result = nvram.set_dip_setting("Right Coin Chute", "1/1");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "No dip switch setting 'Right Coin Chute'"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_factory_defaults() -> io::Result<()> {
+        let map: NvramMap = serde_json::from_value(serde_json::json!({
+            "_fileformat": 0.8,
+            "_metadata": {"platform": "test", "version": 1, "roms": ["test"]},
+            "adjustments": {
+                "Standard": {
+                    "01": {"label": "Balls Per Game", "encoding": "int", "start": 0, "default": 3},
+                    "02": {"label": "Free Play", "encoding": "enum", "start": 1, "values": ["No", "Yes"], "default": "Yes"},
+                    "03": {"label": "Match", "encoding": "bool", "start": 8}
+                }
+            },
+            "high_scores": [
+                {
+                    "label": "Grand Champion",
+                    "initials": {"encoding": "ch", "start": 2, "length": 3, "default": "ABC"},
+                    "score": {"encoding": "bcd", "start": 5, "length": 2, "scale": 10, "default": 12340}
+                }
+            ],
+            "checksum8": [{"start": 0, "end": 7, "label": "Settings"}],
+            "dip_switches": {
+                "coin": {"label": "Coin Chute", "encoding": "dipsw", "offsets": [1, 2], "values": ["A", "B", "C", "D"], "default": "C"}
+            }
+        }))?;
+        let platform = test_util::platform(serde_json::json!([
+            {"label": "NVRAM", "address": "0x0000", "size": "0x0008", "type": "nvram"},
+            {"label": "Extended NVRAM", "address": "0x0010", "size": "0x0002", "type": "nvram"},
+            {"label": "RAM", "address": "0x0100", "size": "0x0100", "type": "ram"}
+        ]));
+        let nvram = factory_defaults(&map, &platform)?;
+        let mut expected = vec![0x03, 0x01, b'A', b'B', b'C', 0x12, 0x34, 0xEF];
+        expected.extend([0x00; 2]);
+        expected.extend([0x01, 0x00, 0x00, 0x00, 0x00, 0x00]);
+        assert_eq!(expected, nvram);
+
+        let map = NvramMap {
+            _ramsize: Some(0x0C),
+            ..map
+        };
+        assert_eq!(
+            0x0C + DIP_SWITCH_BYTES,
+            factory_defaults(&map, &platform)?.len()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_descriptor_overrides() -> io::Result<()> {
+        let dir = testdir!();
+        let mut nvram = test_util::nvram_with_map(
+            &dir,
+            &[0x01, 0x34, 0x12, 0xA3, 0x00, 0x00],
+            serde_json::json!({
+                "game_state": {
+                    "tilted": {"label": "Tilted", "encoding": "bool", "start": 0, "mask": "0x01"},
+                    "game_over": {"label": "Game Over", "encoding": "bool", "start": 0, "mask": "0x80"},
+                    "credits": {"label": "Credits", "encoding": "int", "start": 1, "length": 2, "endian": "little"},
+                    "extra_balls": {"label": "Extra Balls", "encoding": "int", "start": 3, "mask": "0x0F"}
+                }
+            }),
+        )?;
+        let expected = GameState {
+            credits: Some(0x1234),
+            tilted: Some(true),
+            game_over: Some(false),
+            extra_balls: Some(3),
+            ..GameState::default()
+        };
+        assert_eq!(Some(expected), nvram.read_typed_game_state()?);
+        assert_eq!(NvValue::Integer(0x1234), nvram.get("game_state/credits")?);
+
+        nvram.set("game_state/game_over", NvValue::Bool(true))?;
+        nvram.set("game_state/credits", NvValue::Integer(0x5678))?;
+        nvram.set("game_state/extra_balls", NvValue::Integer(5))?;
+        assert_eq!(
+            vec![0x81, 0x78, 0x56, 0xA5, 0x00, 0x00],
+            std::fs::read(&nvram.nv_path)?
+        );
+        let result = nvram.set("game_state/extra_balls", NvValue::Integer(16));
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Value 16 does not fit in mask 0xF"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_player_state() -> io::Result<()> {
+        let dir = testdir!();
+        let mut nvram = test_util::nvram_with_map(
+            &dir,
+            &[0x01, 0x02, 0x03, 0x04, 0x05, 0x06],
+            serde_json::json!({
+                "player_state": {
+                    "_notes": ["Kept for each player"],
+                    "raw": {"label": "Player State", "encoding": "raw", "start": 4, "length": 2},
+                    "locks": [
+                        {"label": "Player 1 Locks", "encoding": "int", "start": 0},
+                        {"label": "Player 2 Locks", "encoding": "int", "start": 1}
+                    ],
+                    "mode": [
+                        {"label": "Player 1 Mode", "encoding": "int", "start": 2},
+                        {"label": "Player 2 Mode", "encoding": "int", "start": "0x100"}
+                    ],
+                    "ball": [
+                        {"label": "Player 1 Ball", "encoding": "int", "start": 3, "offset": 1},
+                        {"label": "Player 2 Ball", "encoding": "int", "start": 0, "offset": 1}
+                    ]
+                }
+            }),
+        )?;
+        let expected = PlayerState {
+            current_player: HashMap::from([("raw".to_string(), NvValue::Bytes(vec![0x05, 0x06]))]),
+            players: vec![
+                HashMap::from([
+                    ("locks".to_string(), NvValue::Integer(1)),
+                    ("mode".to_string(), NvValue::Integer(3)),
+                    ("ball".to_string(), NvValue::Integer(5)),
+                ]),
+                HashMap::from([
+                    ("locks".to_string(), NvValue::Integer(2)),
+                    ("mode".to_string(), NvValue::Unavailable),
+                    ("ball".to_string(), NvValue::Integer(2)),
+                ]),
+            ],
+        };
+        let player_state = nvram.read_player_state()?;
+        assert_eq!(Some(expected), player_state);
+        assert_eq!(
+            nvram.get("player_state/ball/0")?,
+            player_state.unwrap().players[0]["ball"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_last_played() -> io::Result<()> {
+        let platform = test_util::platform(serde_json::json!([
+            {"label": "NVRAM", "address": "0x0000", "size": "0x0008", "type": "nvram"}
+        ]));
+        let mut map = test_util::map(serde_json::json!({}))?;
+        assert_eq!(
+            None,
+            read_last_played(
+                &mut Memory::new(&mut io::Cursor::new(vec![]), None),
+                &platform,
+                &map
+            )?
+        );
+
+        map.last_played = Some(serde_json::from_value(
+            serde_json::json!({"encoding": "wpc_rtc", "start": 1, "length": 7}),
+        )?);
+        let mut nvram = io::Cursor::new(vec![0x00, 0x07, 0xE8, 0x06, 0x13, 0x04, 0x00, 0x01, 0x00]);
+        let mut memory = Memory::new(&mut nvram, None);
+        let expected = Timestamp::new(2024, 6, 19, 0, 1);
+        assert_eq!(expected, read_last_played(&mut memory, &platform, &map)?);
+
+        let mut unset = io::Cursor::new(vec![0xFF; 8]);
+        let mut memory = Memory::new(&mut unset, None);
+        assert_eq!(None, read_last_played(&mut memory, &platform, &map)?);
+
+        map.last_played = Some(serde_json::from_value(
+            serde_json::json!({"encoding": "int", "start": 1}),
+        )?);
+        let result = read_last_played(&mut memory, &platform, &map);
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_descriptor() -> io::Result<()> {
+        let platform = test_util::platform(serde_json::json!([
+            {"label": "RAM", "address": "0x0000", "size": "0x0100", "type": "ram"},
+            {"label": "NVRAM", "address": "0x0100", "size": "0x0004", "type": "nvram"}
+        ]));
+        let map = test_util::map(serde_json::json!({}))?;
+        let mut nvram = io::Cursor::new(vec![0x01, 0x02, 0x41, 0x42]);
+        let mut memory = Memory::new(&mut nvram, None);
+        let mut read = |descriptor: Value| -> io::Result<NvValue> {
+            let descriptor: Descriptor = serde_json::from_value(descriptor)?;
+            resolve_descriptor(&mut memory, &descriptor, &platform, &map)
+        };
+        assert_eq!(
+            NvValue::Integer(0x0102),
+            read(serde_json::json!({"encoding": "int", "start": "0x100", "length": 2}))?
+        );
+        assert_eq!(
+            NvValue::Enum {
+                index: 1,
+                label: Some(Value::from("On"))
+            },
+            read(
+                serde_json::json!({"encoding": "enum", "start": "0x100", "values": ["Off", "On"]})
+            )?
+        );
+        assert_eq!(
+            NvValue::Text("AB".to_string()),
+            read(serde_json::json!({"encoding": "ch", "start": "0x102", "length": 2}))?
+        );
+        assert_eq!(
+            NvValue::Bool(true),
+            read(serde_json::json!({"encoding": "bool", "start": "0x101"}))?
+        );
+        assert_eq!(
+            NvValue::Bytes(vec![0x02, 0x41]),
+            read(serde_json::json!({"encoding": "raw", "start": "0x101", "length": 2}))?
+        );
+        assert_eq!(
+            NvValue::Unavailable,
+            read(serde_json::json!({"encoding": "int", "start": "0x10"}))?
+        );
+        assert_eq!(
+            NvValue::Integer(0),
+            read(serde_json::json!({"encoding": "int", "start": "0x100", "offset": -1}))?
+        );
+        let result = read(
+            serde_json::json!({"encoding": "enum", "start": "0x101", "values": ["Off", "On"]}),
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Failed to resolve: Index 2 out of bounds for enum with 2 values"
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_find_map() -> io::Result<()> {
         let map: Option<Value> = find_map(&"afm_113b".to_string())?;
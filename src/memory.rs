@@ -0,0 +1,202 @@
+use crate::encoding::Location;
+use crate::model::Platform;
+use std::io;
+use std::io::{Read, Seek};
+
+pub(crate) trait ReadSeek: Read + Seek {}
+
+impl<T: Read + Seek> ReadSeek for T {}
+
+pub(crate) enum LocateResult {
+    /// The value is not stored in the .nv file and there is no memory image that holds it
+    OutsideNVRAM,
+    /// The value is stored in the .nv file, the location contains file offsets
+    Located(Location),
+    /// The value lives in another region of the CPU address space, the location contains
+    /// CPU addresses
+    InMemory(Location),
+}
+
+/// Locate a value in the memory of the platform.
+///
+/// Values in the `nvram` and `banked` regions are read from the .nv file, values in any other
+/// region the platform declares can only be read from a memory image. A continuous range must
+/// start and end in the same region, as the regions are not necessarily adjacent in the .nv
+/// file.
+pub(crate) fn locate(platform: &Platform, location: Location) -> io::Result<LocateResult> {
+    if let Location::Continuous { start, length } = location
+        && length > 1
+    {
+        let end = start + length as u64 - 1;
+        if platform.crosses_regions(start, end) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Range {start:#06x}-{end:#06x} crosses a memory region boundary"),
+            ));
+        }
+    }
+    let addresses = match &location {
+        Location::Continuous { start, .. } => vec![*start],
+        Location::Scattered { offsets } => offsets.clone(),
+    };
+    let file_offsets: Option<Vec<u64>> = addresses
+        .iter()
+        .map(|address| platform.file_offset(*address))
+        .collect();
+    let located = match (file_offsets, location) {
+        (Some(file_offsets), Location::Continuous { length, .. }) => {
+            LocateResult::Located(Location::Continuous {
+                start: file_offsets[0],
+                length,
+            })
+        }
+        (Some(file_offsets), Location::Scattered { .. }) => {
+            LocateResult::Located(Location::Scattered {
+                offsets: file_offsets,
+            })
+        }
+        (None, location) => {
+            if addresses.iter().all(|a| platform.region(*a).is_some()) {
+                LocateResult::InMemory(location)
+            } else {
+                LocateResult::OutsideNVRAM
+            }
+        }
+    };
+    Ok(located)
+}
+
+/// The memory that descriptor values are read from.
+///
+/// This is the .nv file, optionally combined with an image of the CPU address space (for
+/// example a RAM dump taken from a running PinMAME) for values that live outside the NVRAM.
+/// The image is indexed by CPU address, starting at address 0.
+pub(crate) struct Memory<'a> {
+    nvram: &'a mut dyn ReadSeek,
+    image: Option<io::Cursor<&'a [u8]>>,
+}
+
+impl<'a> Memory<'a> {
+    pub(crate) fn new(nvram: &'a mut dyn ReadSeek, image: Option<&'a [u8]>) -> Self {
+        Memory {
+            nvram,
+            image: image.map(io::Cursor::new),
+        }
+    }
+
+    /// The .nv file
+    pub(crate) fn nvram(&mut self) -> &mut dyn ReadSeek {
+        self.nvram
+    }
+
+    /// The stream and location to read a located value from, `None` if the value is not
+    /// available.
+    pub(crate) fn stream(
+        &mut self,
+        located: LocateResult,
+    ) -> Option<(&mut dyn ReadSeek, Location)> {
+        match located {
+            LocateResult::OutsideNVRAM => None,
+            LocateResult::Located(location) => Some((self.nvram, location)),
+            LocateResult::InMemory(location) => self
+                .image
+                .as_mut()
+                .map(|image| (image as &mut dyn ReadSeek, location)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::read_int;
+    use crate::model::{Endian, Nibble};
+    use crate::test_util;
+    use pretty_assertions::assert_eq;
+
+    fn platform() -> Platform {
+        test_util::platform(serde_json::json!([
+            {"label": "RAM", "address": "0x0000", "size": "0x0004", "type": "ram"},
+            {"label": "NVRAM", "address": "0x0004", "size": "0x0004", "type": "nvram"}
+        ]))
+    }
+
+    fn read(memory: &mut Memory, address: u64) -> Option<u64> {
+        let located = locate(
+            &platform(),
+            Location::Continuous {
+                start: address,
+                length: 1,
+            },
+        )
+        .unwrap();
+        let (mut stream, location) = memory.stream(located)?;
+        Some(read_int(&mut stream, Endian::Big, Nibble::Both, location, None).unwrap())
+    }
+
+    #[test]
+    fn test_read_nvram_without_image() {
+        let mut nvram = io::Cursor::new(vec![0x10, 0x11, 0x12, 0x13]);
+        let mut memory = Memory::new(&mut nvram, None);
+        assert_eq!(Some(0x11), read(&mut memory, 0x0005));
+        assert_eq!(None, read(&mut memory, 0x0001));
+    }
+
+    #[test]
+    fn test_read_ram_from_image() {
+        let mut nvram = io::Cursor::new(vec![0x10, 0x11, 0x12, 0x13]);
+        let image = [0x20, 0x21, 0x22, 0x23, 0xFF, 0xFF, 0xFF, 0xFF];
+        let mut memory = Memory::new(&mut nvram, Some(&image));
+        assert_eq!(Some(0x21), read(&mut memory, 0x0001));
+        // nvram values still come from the .nv file
+        assert_eq!(Some(0x11), read(&mut memory, 0x0005));
+    }
+
+    #[test]
+    fn test_locate() {
+        let platform = platform();
+        let locate_scattered =
+            |offsets: Vec<u64>| locate(&platform, Location::Scattered { offsets }).unwrap();
+        assert!(matches!(
+            locate_scattered(vec![0x0004, 0x0006]),
+            LocateResult::Located(_)
+        ));
+        assert!(matches!(
+            locate_scattered(vec![0x0001, 0x0005]),
+            LocateResult::InMemory(_)
+        ));
+        assert!(matches!(
+            locate_scattered(vec![0x0001, 0x0100]),
+            LocateResult::OutsideNVRAM
+        ));
+    }
+
+    #[test]
+    fn test_locate_continuous_range() {
+        let platform = test_util::platform(serde_json::json!([
+            {"label": "RAM", "address": "0x0000", "size": "0x0004", "type": "ram"},
+            {"label": "NVRAM", "address": "0x0004", "size": "0x0004", "type": "nvram"},
+            {"label": "Bank", "address": "0x0008", "size": "0x0004", "type": "banked"}
+        ]));
+        let locate_range = |start: u64, length: usize| {
+            locate(&platform, Location::Continuous { start, length })
+                .map(|located| match located {
+                    LocateResult::Located(Location::Continuous { start, .. }) => Some(start),
+                    _ => None,
+                })
+                .map_err(|e| e.to_string())
+        };
+        assert_eq!(Ok(Some(0x0002)), locate_range(0x0006, 2));
+        assert_eq!(Ok(Some(0x0004)), locate_range(0x0008, 4));
+        assert_eq!(
+            Err("Range 0x0006-0x0009 crosses a memory region boundary".to_string()),
+            locate_range(0x0006, 4)
+        );
+        assert_eq!(
+            Err("Range 0x0002-0x0005 crosses a memory region boundary".to_string()),
+            locate_range(0x0002, 4)
+        );
+        // ranges within the RAM are read from a memory image
+        assert_eq!(Ok(None), locate_range(0x0000, 4));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::value::{NvValue, remove_scale};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::{Number, Value};
 use std::collections::HashMap;
@@ -8,6 +9,9 @@ pub const DEFAULT_SCALE: i32 = 1;
 
 pub const DEFAULT_INVERT: bool = false;
 
+/// The BCD nibble that shows as an empty digit, e.g. on Dracula/Wild Fyre
+pub const DEFAULT_BCD_BLANK: u8 = 0xF;
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Copy, Clone)]
 #[serde(rename_all = "lowercase")]
 pub enum MemoryLayoutType {
@@ -34,6 +38,26 @@ impl MemoryLayout {
     pub(crate) fn nibble(&self) -> Nibble {
         self.nibble.unwrap_or(Nibble::Both)
     }
+
+    fn start(&self) -> u64 {
+        (&self.address).into()
+    }
+
+    fn size(&self) -> u64 {
+        (&self.size).into()
+    }
+
+    fn contains(&self, address: u64) -> bool {
+        address >= self.start() && address - self.start() < self.size()
+    }
+
+    /// Whether PinMAME saves this region to the .nv file
+    fn is_persisted(&self) -> bool {
+        matches!(
+            self.type_,
+            MemoryLayoutType::NVRam | MemoryLayoutType::Banked
+        )
+    }
 }
 
 /// A platform defines the native memory layout
@@ -47,38 +71,98 @@ pub struct Platform {
     pub cpu: String,
     pub endian: Endian,
     pub memory_layout: Vec<MemoryLayout>,
+    /// The BCD nibble that is displayed as a blank digit, other nibbles above 9 are invalid
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bcd_blank: Option<HexOrInteger>,
+    /// Whether the high bit of a `ch` character lights its period/comma segment, as on
+    /// alphanumeric displays
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub period_flag: Option<bool>,
 }
 
 impl Platform {
-    // TODO this should probably take a memory address instead
-    pub(crate) fn nibble(&self, memory_layout_type: MemoryLayoutType) -> Nibble {
-        self.layout(memory_layout_type)
-            .nibble
-            .unwrap_or(Nibble::Both)
+    /// The memory region that contains the address, if any
+    pub(crate) fn region(&self, address: u64) -> Option<&MemoryLayout> {
+        self.memory_layout.iter().find(|l| l.contains(address))
+    }
+
+    /// Translate a CPU address to an offset in the .nv file.
+    ///
+    /// Returns `None` if the address is not stored in the .nv file, e.g. because it lives in a
+    /// `ram` or `rom` region.
+    pub(crate) fn file_offset(&self, address: u64) -> Option<u64> {
+        self.persisted_region(address)
+            .map(|(file_start, layout)| file_start + address - layout.start())
+    }
+
+    /// Whether the inclusive range of addresses starts and ends in different memory regions.
+    ///
+    /// An address past the end of a persisted region counts as part of it, see
+    /// [`Platform::file_offset`].
+    pub(crate) fn crosses_regions(&self, start: u64, end: u64) -> bool {
+        match (self.persisted_region(start), self.persisted_region(end)) {
+            (Some((start_offset, _)), Some((end_offset, _))) => start_offset != end_offset,
+            (None, None) => match (self.region(start), self.region(end)) {
+                (Some(start_region), Some(end_region)) => !std::ptr::eq(start_region, end_region),
+                _ => false,
+            },
+            _ => true,
+        }
     }
 
-    // TODO this should probably take a memory address instead
-    pub(crate) fn offset(&self, memory_layout_type: MemoryLayoutType) -> u64 {
-        (&self.layout(memory_layout_type).address).into()
+    /// The BCD nibble that is read as a blank digit, see [`DEFAULT_BCD_BLANK`]
+    pub(crate) fn bcd_blank(&self) -> u8 {
+        self.bcd_blank
+            .as_ref()
+            .map_or(DEFAULT_BCD_BLANK, |blank| u64::from(blank) as u8)
     }
 
-    pub(crate) fn layout(&self, memory_layout_type: MemoryLayoutType) -> &MemoryLayout {
-        if let Some(layout) = self
-            .memory_layout
+    /// The nibble setting of the region that contains the address.
+    pub(crate) fn nibble(&self, address: u64) -> Nibble {
+        self.persisted_region(address)
+            .map(|(_, layout)| layout.nibble())
+            .or_else(|| self.region(address).map(MemoryLayout::nibble))
+            .unwrap_or(Nibble::Both)
+    }
+
+    /// The size of the NVRAM in the .nv file, all `nvram` and `banked` regions
+    pub(crate) fn nvram_size(&self) -> u64 {
+        self.memory_layout
             .iter()
-            .find(|l| l.type_ == memory_layout_type)
+            .filter(|l| l.is_persisted())
+            .map(MemoryLayout::size)
+            .sum()
+    }
+
+    /// Find the persisted region for an address together with the offset in the .nv file
+    /// where that region starts.
+    ///
+    /// PinMAME writes the `nvram` and `banked` regions back to back, in the order the platform
+    /// lists them. An address that is not inside any declared region is attributed to the
+    /// closest persisted region below it, as some maps address bytes past the declared size.
+    fn persisted_region(&self, address: u64) -> Option<(u64, &MemoryLayout)> {
+        if let Some(region) = self.region(address)
+            && !region.is_persisted()
         {
-            layout
-        } else {
-            panic!("Memory layout not found for {memory_layout_type:?}");
+            return None;
         }
+        self.memory_layout
+            .iter()
+            .filter(|l| l.is_persisted())
+            .scan(0, |file_start, layout| {
+                let start = *file_start;
+                *file_start += layout.size();
+                Some((start, layout))
+            })
+            .filter(|(_, layout)| layout.start() <= address)
+            .max_by_key(|(_, layout)| layout.start())
     }
 }
 
 /// Descriptor for a single value in the NVRAM.
 /// Describing a section of the .nv file and how to interpret it
 ///
-/// see https://github.com/tomlogic/pinmame-nvram-maps?tab=readme-ov-file#descriptors
+/// see <https://github.com/tomlogic/pinmame-nvram-maps?tab=readme-ov-file#descriptors>
 #[derive(Serialize, Deserialize)]
 pub struct Descriptor {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -122,12 +206,90 @@ pub struct Descriptor {
     pub offsets: Option<Vec<HexOrInteger>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub null: Option<Null>,
+    /// overrides the `period_flag` of the platform for a `ch` descriptor
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub period_flag: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub units: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub invert: Option<bool>,
 }
 
+impl Descriptor {
+    /// Check a value against the `min`, `max` and `multiple_of` of the descriptor.
+    ///
+    /// These apply to the stored value, before `scale` and `offset`, and to the index of
+    /// an `enum`. Other values, and integers that can not be stored with this `scale` at
+    /// all, are not checked here.
+    pub fn validate(&self, value: &NvValue) -> Result<(), ValidationError> {
+        let stored = match value {
+            NvValue::Integer(display) => {
+                let default_scale = Number::from(DEFAULT_SCALE);
+                let scale = self.scale.as_ref().unwrap_or(&default_scale);
+                let scaled = display.checked_sub(i128::from(self.offset.unwrap_or(0)));
+                match scaled {
+                    Some(scaled) => remove_scale(scale, scaled)
+                        .map_err(|_| ValidationError::UnsupportedScale(scale.clone()))?,
+                    None => None,
+                }
+            }
+            NvValue::Enum { index, .. } => Some(*index as u64),
+            _ => None,
+        };
+        let Some(value) = stored else {
+            return Ok(());
+        };
+        if self.min.is_some_and(|min| value < min) || self.max.is_some_and(|max| value > max) {
+            return Err(ValidationError::OutOfRange {
+                min: self.min,
+                max: self.max,
+                value,
+            });
+        }
+        match self.multiple_of {
+            Some(multiple_of) if multiple_of != 0 => {
+                if value % multiple_of.unsigned_abs() != 0 {
+                    return Err(ValidationError::NotMultipleOf { multiple_of, value });
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A value that does not meet the constraints of its descriptor, see [`Descriptor::validate`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationError {
+    /// The stored value is outside the `min` and/or `max` of the descriptor
+    OutOfRange {
+        min: Option<u64>,
+        max: Option<u64>,
+        value: u64,
+    },
+    /// The stored value is not a multiple of the `multiple_of` of the descriptor
+    NotMultipleOf { multiple_of: i64, value: u64 },
+    /// The `scale` of the descriptor is not a number the value can be divided by
+    UnsupportedScale(Number),
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::OutOfRange { min, max, value } => match (min, max) {
+                (Some(min), Some(max)) => write!(f, "Value out of range: {min} ≤ {value} ≤ {max}"),
+                (Some(min), None) => write!(f, "Value out of range: {min} ≤ {value}"),
+                (None, Some(max)) => write!(f, "Value out of range: {value} ≤ {max}"),
+                (None, None) => write!(f, "Value out of range: {value}"),
+            },
+            ValidationError::NotMultipleOf { multiple_of, value } => {
+                write!(f, "Value {value} is not a multiple of {multiple_of}")
+            }
+            ValidationError::UnsupportedScale(scale) => write!(f, "Unsupported scale {scale}"),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
 pub struct Checksum16 {
     pub start: HexOrInteger,
@@ -188,6 +350,23 @@ pub enum Encoding {
     Bool,
 }
 
+impl fmt::Display for Encoding {
+    /// The name of the encoding in the map, e.g. `wpc_rtc`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Encoding::Enum => "enum",
+            Encoding::Int => "int",
+            Encoding::Bits => "bits",
+            Encoding::Bcd => "bcd",
+            Encoding::Ch => "ch",
+            Encoding::Raw => "raw",
+            Encoding::WpcRtc => "wpc_rtc",
+            Encoding::Dipsw => "dipsw",
+            Encoding::Bool => "bool",
+        })
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Copy, Clone)]
 #[serde(rename_all = "lowercase")]
 pub enum Null {
@@ -413,9 +592,9 @@ pub struct NvramMap {
     pub limits: Option<HashMap<String, String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub game_state: Option<HashMap<String, StateOrStateList>>,
-    /// TODO for now we don't do anything with this, so we keep it generic
+    /// Lists have a descriptor for each player, single descriptors are kept for the player that is up
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub player_state: Option<Value>,
+    pub player_state: Option<HashMap<String, StateOrStateList>>,
     /// TODO this HashMap<String, StateOrStateList>should probably be removed as it is an adjustment and only used in ww_l5.nv.json
     #[serde(skip_serializing_if = "Option::is_none")]
     pub replay_score: Option<Descriptor>,
@@ -483,11 +662,100 @@ impl GlobalSettings for GlobalSettingsImpl {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util;
     use pretty_assertions::assert_eq;
     use serde_json::Value;
     use std::path::Path;
     use walkdir::WalkDir;
 
+    fn descriptor(value: Value) -> Descriptor {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_validate_in_range() {
+        let volume = descriptor(serde_json::json!({"encoding": "int", "min": 0, "max": 31}));
+        assert_eq!(volume.validate(&NvValue::Integer(15)), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_out_of_range() {
+        let volume = descriptor(serde_json::json!({"encoding": "int", "min": 0, "max": 31}));
+        assert_eq!(
+            volume.validate(&NvValue::Integer(255)),
+            Err(ValidationError::OutOfRange {
+                min: Some(0),
+                max: Some(31),
+                value: 255
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate_non_numeric_is_skipped() {
+        // values without a stored number, like special value labels, are not checked
+        let volume = descriptor(serde_json::json!({"encoding": "int", "min": 0, "max": 31}));
+        assert_eq!(volume.validate(&NvValue::Text("OFF".to_string())), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_no_bounds() {
+        // a descriptor without min/max is never range-checked
+        let unbounded = descriptor(serde_json::json!({"encoding": "int"}));
+        assert_eq!(unbounded.validate(&NvValue::Integer(255)), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_one_sided() {
+        let at_least = descriptor(serde_json::json!({"encoding": "int", "min": 3}));
+        assert_eq!(at_least.validate(&NvValue::Integer(3)), Ok(()));
+        let result = at_least.validate(&NvValue::Integer(2));
+        assert_eq!(result.unwrap_err().to_string(), "Value out of range: 3 ≤ 2");
+        let at_most = descriptor(serde_json::json!({"encoding": "enum", "max": 1}));
+        let result = at_most.validate(&NvValue::Enum {
+            index: 2,
+            label: None,
+        });
+        assert_eq!(result.unwrap_err().to_string(), "Value out of range: 2 ≤ 1");
+    }
+
+    #[test]
+    fn test_validate_multiple_of() {
+        let replay = descriptor(serde_json::json!({"encoding": "int", "multiple_of": 5}));
+        assert_eq!(replay.validate(&NvValue::Integer(15)), Ok(()));
+        assert_eq!(
+            replay.validate(&NvValue::Integer(12)),
+            Err(ValidationError::NotMultipleOf {
+                multiple_of: 5,
+                value: 12
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate_scale_and_offset() {
+        // the bounds apply to the stored value, 12 * 0.5 - 2 = 4
+        let adjustment = descriptor(serde_json::json!({
+            "encoding": "int", "scale": 0.5, "offset": -2, "min": 4, "max": 20, "multiple_of": 4
+        }));
+        assert_eq!(adjustment.validate(&NvValue::Integer(4)), Ok(()));
+        assert_eq!(
+            adjustment.validate(&NvValue::Integer(3)),
+            Err(ValidationError::NotMultipleOf {
+                multiple_of: 4,
+                value: 10
+            })
+        );
+        assert_eq!(
+            adjustment.validate(&NvValue::Integer(-1)),
+            Err(ValidationError::OutOfRange {
+                min: Some(4),
+                max: Some(20),
+                value: 2
+            })
+        );
+    }
+
     #[test]
     fn read_all_nvram_maps() {
         // read all ../pinmame-nvram-maps/*.json recursively
@@ -541,6 +809,45 @@ mod tests {
         assert!(found_any, "No platform files found");
     }
 
+    #[test]
+    fn test_platform_single_nvram_region() {
+        let platform = test_util::platform(serde_json::json!([
+            {"label": "RAM", "address": "0x0000", "size": "0x0100", "type": "ram"},
+            {"label": "NVRAM", "address": "0x0100", "size": "0x0100", "type": "nvram", "nibble": "low"}
+        ]));
+        assert_eq!(None, platform.file_offset(0x0050));
+        assert_eq!(Some(0x0000), platform.file_offset(0x0100));
+        assert_eq!(Some(0x0042), platform.file_offset(0x0142));
+        assert_eq!(Nibble::Both, platform.nibble(0x0050));
+        assert_eq!(Nibble::Low, platform.nibble(0x0142));
+    }
+
+    #[test]
+    fn test_platform_address_past_declared_size() {
+        let platform = test_util::platform(serde_json::json!([
+            {"label": "NVRAM", "address": "0x0100", "size": "0x0100", "type": "nvram"}
+        ]));
+        assert_eq!(None, platform.file_offset(0x00FF));
+        assert_eq!(Some(0x0180), platform.file_offset(0x0280));
+    }
+
+    #[test]
+    fn test_platform_multiple_regions() {
+        let platform = test_util::platform(serde_json::json!([
+            {"label": "NVRAM 1", "address": "0x1000", "size": "0x0800", "type": "nvram"},
+            {"label": "RAM", "address": "0x2000", "size": "0x1000", "type": "ram"},
+            {"label": "Bank", "address": "0x4000", "size": "0x0400", "type": "banked", "nibble": "high"},
+            {"label": "NVRAM 2", "address": "0x3000", "size": "0x0200", "type": "nvram"}
+        ]));
+        assert_eq!(Some(0x0010), platform.file_offset(0x1010));
+        assert_eq!(None, platform.file_offset(0x2010));
+        // regions are stored in the order they are declared, not by address
+        assert_eq!(Some(0x0810), platform.file_offset(0x4010));
+        assert_eq!(Some(0x0C10), platform.file_offset(0x3010));
+        assert_eq!(Nibble::High, platform.nibble(0x4010));
+        assert_eq!(Nibble::Both, platform.nibble(0x3010));
+    }
+
     #[test]
     fn test_hex_or_integer_partial_eq() {
         let hex1 = HexOrInteger::Hex(HexString {
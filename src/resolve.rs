@@ -1,19 +1,447 @@
-use crate::checksum::{verify_checksum8, verify_checksum16};
+use crate::checksum::{ChecksumMismatch, verify_checksum8, verify_checksum16};
 use crate::encoding::{
-    Location, read_bcd, read_bool, read_ch, read_exact_at, read_int, read_wpc_rtc,
+    Charset, Location, read_bcd, read_bool, read_ch, read_int, read_location, read_wpc_rtc,
+    write_bcd, write_ch, write_ch_bytes, write_int, write_location, write_wpc_rtc,
 };
+use crate::memory::{LocateResult, Memory, ReadSeek, locate};
 use crate::model::{
-    Checksum8, Checksum16, DEFAULT_INVERT, DEFAULT_LENGTH, DEFAULT_SCALE, Encoding, Endian,
-    GlobalSettings, GlobalSettingsImpl, MemoryLayout, MemoryLayoutType, Nibble, Null, Platform,
+    Checksum8, Checksum16, DEFAULT_INVERT, DEFAULT_LENGTH, DEFAULT_SCALE, Descriptor, Encoding,
+    Endian, GlobalSettings, GlobalSettingsImpl, Nibble, Null, Platform, ValidationError,
 };
+use crate::value::{NvValue, apply_offset, apply_scale, remove_scale};
 use crate::{dips, open_nvram, read_platform};
+use serde::{Serialize, Serializer};
 use serde_json::{Map, Number, Value};
+use std::collections::BTreeMap;
+use std::fmt;
 use std::fs::OpenOptions;
 use std::io;
-use std::io::{Read, Seek};
+use std::io::{Read, Seek, Write};
 use std::path::Path;
 
+/// A resolved NVRAM file.
+///
+/// Mirrors the structure of the map, where every descriptor is replaced by a
+/// [`ResolvedEntry`] holding its decoded value. Serializes to the same JSON as
+/// the `.nv.json` files in the testdata folder.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct ResolvedNvram {
+    pub sections: BTreeMap<String, ResolvedNode>,
+}
+
+impl ResolvedNvram {
+    pub fn get(&self, key: &str) -> Option<&ResolvedNode> {
+        self.sections.get(key)
+    }
+
+    /// All warnings in the resolved NVRAM, in map order
+    pub fn warnings(&self) -> Vec<DescriptorWarning> {
+        let mut warnings = Vec::new();
+        for (key, node) in &self.sections {
+            collect_warnings(key.clone(), node, &mut warnings);
+        }
+        warnings
+    }
+}
+
+fn collect_warnings(path: String, node: &ResolvedNode, out: &mut Vec<DescriptorWarning>) {
+    match node {
+        ResolvedNode::Entry(entry) => {
+            if let Some(warning) = &entry.warning {
+                out.push(DescriptorWarning {
+                    path,
+                    label: entry.label.clone(),
+                    warning: warning.clone(),
+                });
+            }
+        }
+        ResolvedNode::Section(section) => {
+            for (key, node) in section {
+                collect_warnings(format!("{path}/{key}"), node, out);
+            }
+        }
+        ResolvedNode::List(list) => {
+            for (index, node) in list.iter().enumerate() {
+                collect_warnings(format!("{path}/{index}"), node, out);
+            }
+        }
+        ResolvedNode::Checksums(_) | ResolvedNode::Other(_) => {}
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum ResolvedNode {
+    Entry(ResolvedEntry),
+    Checksums(Vec<ResolvedChecksum>),
+    Section(BTreeMap<String, ResolvedNode>),
+    List(Vec<ResolvedNode>),
+    /// Anything else in the map that is copied as is, like notes
+    Other(Value),
+}
+
+impl ResolvedNode {
+    /// Get a child of a section by key
+    pub fn get(&self, key: &str) -> Option<&ResolvedNode> {
+        match self {
+            ResolvedNode::Section(section) => section.get(key),
+            _ => None,
+        }
+    }
+
+    pub fn as_entry(&self) -> Option<&ResolvedEntry> {
+        match self {
+            ResolvedNode::Entry(entry) => Some(entry),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[ResolvedNode]> {
+        match self {
+            ResolvedNode::List(list) => Some(list),
+            _ => None,
+        }
+    }
+}
+
+/// A resolved descriptor
+///
+/// The fields are declared in alphabetical order to keep the JSON output stable.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ResolvedEntry {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// The bytes of text with undecoded characters, see [`NvValue::UndecodedText`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw: Option<Vec<u8>>,
+    // Surface the map's unit annotation on resolved numeric fields
+    // so downstream consumers can render time-like / distance-like
+    // scores with the right formatting (e.g. `units: "seconds"`
+    // -> `mm:ss.dd`) without hardcoding per-table knowledge.
+    // `scale` is intentionally not propagated: `value` is already
+    // post-scaled, so exposing it would just invite double-scaling
+    // bugs. See issue #124.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub units: Option<String>,
+    /// `None` if the value could not be resolved, see `warning`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<NvValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<ResolveWarning>,
+}
+
+/// A problem found while resolving a descriptor
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResolveWarning {
+    /// The value does not meet the constraints of the descriptor, see [`Descriptor::validate`]
+    Invalid(ValidationError),
+    /// The value is not stored in the NVRAM and there is no memory image to read it from
+    OutsideNvram,
+    /// The index read for an `enum` has no entry in its `values`
+    UnknownEnumIndex { index: usize, count: usize },
+    /// The value could not be decoded, for example because the descriptor is invalid
+    DecodeError(String),
+    /// The date or time does not exist, usually because the clock was never set
+    InvalidTimestamp,
+    /// Values with this encoding can not be decoded yet
+    Unsupported(Encoding),
+}
+
+impl fmt::Display for ResolveWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResolveWarning::Invalid(error) => write!(f, "{error}"),
+            ResolveWarning::OutsideNvram => {
+                write!(f, "Failed to resolve: Value is stored outside the NVRAM")
+            }
+            ResolveWarning::UnknownEnumIndex { index, count } => write!(
+                f,
+                "Failed to resolve: Index {index} out of bounds for enum with {count} values"
+            ),
+            ResolveWarning::DecodeError(message) => write!(f, "Failed to resolve: {message}"),
+            ResolveWarning::InvalidTimestamp => write!(f, "Invalid timestamp"),
+            ResolveWarning::Unsupported(encoding) => {
+                write!(f, "Reading {encoding} values is not supported")
+            }
+        }
+    }
+}
+
+impl Serialize for ResolveWarning {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl From<ValidationError> for ResolveWarning {
+    fn from(error: ValidationError) -> Self {
+        ResolveWarning::Invalid(error)
+    }
+}
+
+impl From<ResolveWarning> for io::Error {
+    fn from(warning: ResolveWarning) -> Self {
+        let kind = match warning {
+            ResolveWarning::Unsupported(_) => io::ErrorKind::Unsupported,
+            _ => io::ErrorKind::InvalidData,
+        };
+        io::Error::new(kind, warning.to_string())
+    }
+}
+
+impl From<io::Error> for ResolveWarning {
+    fn from(e: io::Error) -> Self {
+        ResolveWarning::DecodeError(e.to_string())
+    }
+}
+
+/// A warning together with the descriptor it was found for
+#[derive(Debug, Clone, PartialEq)]
+pub struct DescriptorWarning {
+    /// The path of the descriptor in the map, keys and array indexes joined by `/`,
+    /// e.g. `mode_champions/3/score`, see [`resolve_selected`]
+    pub path: String,
+    pub label: Option<String>,
+    pub warning: ResolveWarning,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChecksumStatus {
+    Valid,
+    Mismatch,
+}
+
+/// The result of verifying a `checksum8` or `checksum16` entry
+///
+/// The fields are declared in alphabetical order to keep the JSON output stable.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ResolvedChecksum {
+    #[serde(
+        rename = "checksum_mismatch_calculated",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub calculated: Option<u64>,
+    #[serde(
+        rename = "checksum_mismatch_expected",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub expected: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(rename = "value")]
+    pub status: ChecksumStatus,
+}
+
+impl ResolvedChecksum {
+    fn new<T: Into<u64>>(label: Option<String>, mismatch: Option<ChecksumMismatch<T>>) -> Self {
+        match mismatch {
+            Some(mismatch) => ResolvedChecksum {
+                calculated: Some(mismatch.calculated.into()),
+                expected: Some(mismatch.expected.into()),
+                label,
+                status: ChecksumStatus::Mismatch,
+            },
+            None => ResolvedChecksum {
+                calculated: None,
+                expected: None,
+                label,
+                status: ChecksumStatus::Valid,
+            },
+        }
+    }
+}
+
+/// Resolve a NVRAM file to JSON, see [`resolve_typed`] for the typed result.
 pub fn resolve(nv_path: &Path) -> io::Result<Option<Value>> {
+    to_json(resolve_typed(nv_path)?)
+}
+
+/// Resolve a NVRAM file, `None` if there is no map for it.
+pub fn resolve_typed(nv_path: &Path) -> io::Result<Option<ResolvedNvram>> {
+    resolve_nvram(nv_path, None)
+}
+
+/// Resolve a NVRAM file together with an image of the CPU address space to JSON, see
+/// [`resolve_typed_with_memory_image`] for the typed result.
+pub fn resolve_with_memory_image(nv_path: &Path, memory_image: &[u8]) -> io::Result<Option<Value>> {
+    to_json(resolve_typed_with_memory_image(nv_path, memory_image)?)
+}
+
+/// Resolve a NVRAM file together with an image of the CPU address space.
+///
+/// The image starts at address 0, for example a RAM dump of a running PinMAME. Values the map
+/// places in other memory regions than the NVRAM, like most of the `game_state`, are read from
+/// the image instead of being reported as stored outside the NVRAM.
+pub fn resolve_typed_with_memory_image(
+    nv_path: &Path,
+    memory_image: &[u8],
+) -> io::Result<Option<ResolvedNvram>> {
+    resolve_nvram(nv_path, Some(memory_image))
+}
+
+fn to_json(resolved: Option<ResolvedNvram>) -> io::Result<Option<Value>> {
+    Ok(resolved.map(serde_json::to_value).transpose()?)
+}
+
+/// All warnings found while resolving a NVRAM file, see [`ResolvedNvram::warnings`].
+///
+/// Returns an empty list if there is no map for the file.
+pub fn resolve_warnings(nv_path: &Path) -> io::Result<Vec<DescriptorWarning>> {
+    Ok(resolve_typed(nv_path)?
+        .map(|resolved| resolved.warnings())
+        .unwrap_or_default())
+}
+
+/// Resolve only the descriptors matching one of the selectors.
+///
+/// A selector is a path into the map, with segments separated by `/`, or by `.` if the
+/// selector contains no `/`. A segment matches a key, a list index, the `label` of a
+/// descriptor or section, or anything for `*`. For example `adjustments/*/Free Play` or
+/// `game_state.credits`. A selector that matches a section resolves the whole section.
+///
+/// The result only contains the selected parts of the map. List items that are not selected
+/// are kept as `null` so the indexes of the other items do not change.
+pub fn resolve_selected(nv_path: &Path, selectors: &[&str]) -> io::Result<Option<ResolvedNvram>> {
+    let selectors: Vec<Vec<String>> = selectors.iter().map(|s| parse_selector(s)).collect();
+    let selection = Selection::Paths(selectors.iter().map(Vec::as_slice).collect());
+    resolve_nvram_selection(nv_path, None, selection)
+}
+
+fn parse_selector(selector: &str) -> Vec<String> {
+    let separator = if selector.contains('/') { '/' } else { '.' };
+    selector
+        .split(separator)
+        .filter(|segment| !segment.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// The part of the map to resolve.
+#[derive(Clone)]
+enum Selection<'s> {
+    All,
+    /// The remaining segments of the selectors that matched so far
+    Paths(Vec<&'s [String]>),
+}
+
+impl<'s> Selection<'s> {
+    /// The selection for a child of a section or list, `None` if nothing below it is selected.
+    fn child(&self, key: &str, value: &Value) -> Option<Selection<'s>> {
+        let Selection::Paths(paths) = self else {
+            return Some(Selection::All);
+        };
+        let label = value.get("label").and_then(Value::as_str);
+        let mut remaining = Vec::new();
+        for path in paths {
+            let Some((segment, rest)) = path.split_first() else {
+                return Some(Selection::All);
+            };
+            if segment == "*" || segment == key || Some(segment.as_str()) == label {
+                if rest.is_empty() {
+                    return Some(Selection::All);
+                }
+                remaining.push(rest);
+            }
+        }
+        (!remaining.is_empty()).then_some(Selection::Paths(remaining))
+    }
+}
+
+/// Find the descriptor at a path in the map.
+///
+/// The path uses the same segments as the selectors of [`resolve_selected`], without wildcards.
+pub(crate) fn find_descriptor<'m>(
+    map: &'m Value,
+    path: &str,
+) -> io::Result<&'m Map<String, Value>> {
+    let not_found = || {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No descriptor found for path '{path}'"),
+        )
+    };
+    let has_label =
+        |value: &Value, label: &str| value.get("label").and_then(Value::as_str) == Some(label);
+    let mut node = map;
+    for segment in parse_selector(path) {
+        node = match node {
+            Value::Object(object) => object
+                .get(&segment)
+                .or_else(|| object.values().find(|child| has_label(child, &segment))),
+            Value::Array(array) => segment
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| array.get(index))
+                .or_else(|| array.iter().find(|child| has_label(child, &segment))),
+            _ => None,
+        }
+        .ok_or_else(not_found)?;
+    }
+    match node {
+        Value::Object(descriptor) if descriptor.contains_key("encoding") => Ok(descriptor),
+        _ => Err(not_found()),
+    }
+}
+
+/// All descriptors below a node of the map, with their path for [`find_descriptor`].
+///
+/// Like when resolving, keys that start with `_` are skipped.
+pub(crate) fn descriptors<'m>(
+    value: &'m Value,
+    path: &str,
+    found: &mut Vec<(String, &'m Map<String, Value>)>,
+) {
+    let child_path = |key: &str| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{path}/{key}")
+        }
+    };
+    match value {
+        Value::Object(map) if map.contains_key("encoding") => found.push((path.to_string(), map)),
+        Value::Object(map) => map
+            .iter()
+            .filter(|(key, _)| !key.starts_with('_'))
+            .for_each(|(key, value)| descriptors(value, &child_path(key), found)),
+        Value::Array(values) => values
+            .iter()
+            .enumerate()
+            .for_each(|(index, value)| descriptors(value, &child_path(&index.to_string()), found)),
+        _ => {}
+    }
+}
+
+/// The `default` of a descriptor as a value for [`write_value`], if it has one.
+///
+/// A number is the displayed value, or the index for an `enum`. A string is a label, a
+/// `special_values` label for numbers or the text for `ch`.
+pub(crate) fn default_value(descriptor: &Map<String, Value>) -> Option<NvValue> {
+    let encoding: Encoding = serde_json::from_value(descriptor.get("encoding")?.clone()).ok()?;
+    match (encoding, descriptor.get("default")?) {
+        (Encoding::Bool, Value::Number(number)) => Some(NvValue::Bool(number.as_u64() != Some(0))),
+        (Encoding::Enum, Value::Number(number)) => Some(NvValue::Enum {
+            index: usize::try_from(number.as_u64()?).ok()?,
+            label: None,
+        }),
+        (_, Value::Number(number)) => Some(NvValue::Integer(i128::from(number.as_i64()?))),
+        (_, Value::String(text)) => Some(NvValue::Text(text.clone())),
+        (_, Value::Bool(flag)) => Some(NvValue::Bool(*flag)),
+        _ => None,
+    }
+}
+
+fn resolve_nvram(nv_path: &Path, memory_image: Option<&[u8]>) -> io::Result<Option<ResolvedNvram>> {
+    resolve_nvram_selection(nv_path, memory_image, Selection::All)
+}
+
+fn resolve_nvram_selection(
+    nv_path: &Path,
+    memory_image: Option<&[u8]>,
+    selection: Selection,
+) -> io::Result<Option<ResolvedNvram>> {
     let map: Option<Value> = open_nvram(nv_path)?;
     let result = if let Some(map) = &map {
         // TODO how can we do this without cloning the whole object?
@@ -28,8 +456,22 @@ pub fn resolve(nv_path: &Path) -> io::Result<Option<Value>> {
         let platform: Platform = read_platform(global_settings.platform())?;
 
         let mut rom = OpenOptions::new().read(true).open(nv_path)?;
-        match resolve_recursive(map, &global_settings, &platform, &mut rom) {
-            Ok(resolved) => Some(resolved),
+        let mut memory = Memory::new(&mut rom, memory_image);
+        match resolve_recursive(map, &global_settings, &platform, &mut memory, &selection) {
+            Ok(Some(ResolvedNode::Section(sections))) => Some(ResolvedNvram { sections }),
+            // nothing selected
+            Ok(None) => Some(ResolvedNvram {
+                sections: BTreeMap::new(),
+            }),
+            Ok(Some(_)) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "Failed to resolve: {}: map is not an object",
+                        nv_path.display()
+                    ),
+                ));
+            }
             Err(e) => {
                 return Err(io::Error::new(
                     e.kind(),
@@ -43,88 +485,109 @@ pub fn resolve(nv_path: &Path) -> io::Result<Option<Value>> {
     Ok(result)
 }
 
-fn resolve_recursive<T: Read + Seek, S: GlobalSettings>(
+/// Resolve the selected part of a map value, `None` if nothing in it is selected.
+fn resolve_recursive<S: GlobalSettings>(
     value: &Value,
     global_settings: &S,
     platform: &Platform,
-    rom: &mut T,
-) -> io::Result<Value> {
-    let result: Value = match value {
+    memory: &mut Memory,
+    selection: &Selection,
+) -> io::Result<Option<ResolvedNode>> {
+    let result = match value {
         Value::Object(map) => {
-            // println!("{:?}", map);
-            // println!("{:?}", map.get("encoding"));
             if let Some(encoding) = map.get("encoding") {
-                let encoding: Encoding = serde_json::from_value(encoding.clone())?;
-                let resolved = resolve_value(rom, map, encoding, global_settings, platform);
-                let warning = match &resolved {
-                    Ok((_, range_value)) => validate_range(map, *range_value),
-                    Err(e) => Some(format!("Failed to resolve: {e}")),
-                };
-                let mut resolved_map = Map::new();
-                if let Ok((value, _)) = resolved {
-                    resolved_map.insert("value".to_string(), value);
+                if let Selection::Paths(_) = selection {
+                    // the selectors point below this descriptor
+                    return Ok(None);
                 }
-                if let Some(label) = map.get("label") {
+                let resolved = serde_json::from_value::<Encoding>(encoding.clone())
+                    .map_err(|e| ResolveWarning::DecodeError(e.to_string()))
+                    .and_then(|encoding| {
+                        resolve_value(memory, map, encoding, global_settings, platform)
+                    });
+                let warning = resolved_warning(map, &resolved);
+                let raw = match &resolved {
+                    Ok(NvValue::UndecodedText { raw, .. }) => Some(raw.clone()),
+                    _ => None,
+                };
+                ResolvedNode::Entry(ResolvedEntry {
                     // maybe we should instead remove all properties related to the encoding
-                    resolved_map.insert("label".to_string(), label.clone());
-                }
-                // Surface the map's unit annotation on resolved numeric fields
-                // so downstream consumers can render time-like / distance-like
-                // scores with the right formatting (e.g. `units: "seconds"`
-                // -> `mm:ss.dd`) without hardcoding per-table knowledge.
-                // `scale` is intentionally not propagated: `value` is already
-                // post-scaled, so exposing it would just invite double-scaling
-                // bugs. See issue #124.
-                if let Some(units) = map.get("units") {
-                    resolved_map.insert("units".to_string(), units.clone());
-                }
-                if let Some(warning) = warning {
-                    resolved_map.insert("warning".to_string(), Value::String(warning));
-                }
-                Value::Object(resolved_map)
+                    label: map.get("label").and_then(Value::as_str).map(str::to_string),
+                    raw,
+                    units: map.get("units").and_then(Value::as_str).map(str::to_string),
+                    value: resolved.ok(),
+                    warning,
+                })
             } else {
-                let mut resolved_map = Map::new();
+                let mut resolved_map = BTreeMap::new();
                 for (key, value) in map.iter() {
+                    if key.starts_with('_') && key != "_fileformat" {
+                        continue;
+                    }
+                    let Some(child_selection) = selection.child(key, value) else {
+                        continue;
+                    };
                     if key.eq("checksum16") {
-                        let checksum_result = resolve_checksum16(
-                            platform.endian,
-                            rom,
-                            value,
-                            platform.offset(MemoryLayoutType::NVRam),
-                        )?;
-                        resolved_map.insert(key.clone(), checksum_result);
+                        let checksum_result =
+                            resolve_checksum16(platform, &mut memory.nvram(), value)?;
+                        resolved_map.insert(key.clone(), ResolvedNode::Checksums(checksum_result));
                     } else if key.eq("checksum8") {
-                        let checksum_result = resolve_checksum8(rom, value)?;
-                        resolved_map.insert(key.clone(), checksum_result);
-                    } else if key.eq("_fileformat") || !key.starts_with('_') {
-                        resolved_map.insert(
-                            key.clone(),
-                            resolve_recursive(value, global_settings, platform, rom)?,
-                        );
+                        let checksum_result =
+                            resolve_checksum8(platform, &mut memory.nvram(), value)?;
+                        resolved_map.insert(key.clone(), ResolvedNode::Checksums(checksum_result));
+                    } else if let Some(node) = resolve_recursive(
+                        value,
+                        global_settings,
+                        platform,
+                        memory,
+                        &child_selection,
+                    )? {
+                        resolved_map.insert(key.clone(), node);
                     }
                 }
-                Value::Object(resolved_map)
+                if resolved_map.is_empty() && matches!(selection, Selection::Paths(_)) {
+                    return Ok(None);
+                }
+                ResolvedNode::Section(resolved_map)
             }
         }
         Value::Array(array) => {
-            let resolved_array: Vec<Value> = array
-                .iter()
-                .map(|v| resolve_recursive(v, global_settings, platform, rom))
-                .collect::<Result<Vec<_>, _>>()?;
-            Value::Array(resolved_array)
+            let mut resolved_array = Vec::new();
+            let mut any_selected = false;
+            for (index, value) in array.iter().enumerate() {
+                let resolved = match selection.child(&index.to_string(), value) {
+                    Some(child_selection) => resolve_recursive(
+                        value,
+                        global_settings,
+                        platform,
+                        memory,
+                        &child_selection,
+                    )?,
+                    None => None,
+                };
+                any_selected |= resolved.is_some();
+                resolved_array.push(resolved.unwrap_or(ResolvedNode::Other(Value::Null)));
+            }
+            if !any_selected && matches!(selection, Selection::Paths(_)) {
+                return Ok(None);
+            }
+            ResolvedNode::List(resolved_array)
         }
-        other => other.clone(),
+        other => match selection {
+            Selection::All => ResolvedNode::Other(other.clone()),
+            Selection::Paths(_) => return Ok(None),
+        },
     };
-    Ok(result)
+    Ok(Some(result))
 }
 
 /// The `offset` property: a value added to a decoded `int`/`bcd` value before
 /// display (applied after `scale`). Defaults to 0.
-fn value_offset(descriptor: &Map<String, Value>) -> i128 {
+fn value_offset(descriptor: &Map<String, Value>) -> i64 {
     descriptor
         .get("offset")
         .and_then(|v| v.as_i64())
-        .unwrap_or(0) as i128
+        .unwrap_or(0)
 }
 
 /// If the descriptor has a `special_values` override for this displayed integer
@@ -139,130 +602,91 @@ fn special_value(descriptor: &Map<String, Value>, value: i128) -> Option<String>
         .map(str::to_string)
 }
 
-fn number_from_i128(v: i128) -> Number {
-    if (0..=u64::MAX as i128).contains(&v) {
-        Number::from(v as u64)
-    } else {
-        Number::from(v as i64)
+/// The warning to report for a resolved descriptor, if any
+fn resolved_warning(
+    map: &Map<String, Value>,
+    resolved: &Result<NvValue, ResolveWarning>,
+) -> Option<ResolveWarning> {
+    match resolved {
+        Ok(NvValue::Timestamp(timestamp)) if !timestamp.is_valid() => {
+            Some(ResolveWarning::InvalidTimestamp)
+        }
+        Ok(value) => validate_value(map, value).err(),
+        Err(warning) => Some(warning.clone()),
     }
 }
 
-/// Validate the raw numeric value against the descriptor's min and max range.
-/// If the value is out of range a warning is returned.
+/// Validate a resolved value with [`Descriptor::validate`].
 ///
-/// `value` is the raw number the range applies to: the integer for `int`/`bcd`
-/// encodings, or the selected index for `enum` encodings. Encodings that do not
-/// produce a number pass `None` and are never range-checked (they also never
-/// carry min/max).
-fn validate_range(map: &Map<String, Value>, value: Option<u64>) -> Option<String> {
-    let (Some(min), Some(max)) = (map.get("min"), map.get("max")) else {
-        return None;
-    };
-    let number_value = value?;
-    // min and max are unscaled values so we need to unscale the value first
-    let unscaled_value = if let Some(scale) = map.get("scale") {
-        let scale = scale.as_u64().unwrap();
-        number_value / scale
-    } else {
-        number_value
-    };
+/// A descriptor that does not fit the [`Descriptor`] model is a [`ResolveWarning::DecodeError`].
+fn validate_value(map: &Map<String, Value>, value: &NvValue) -> Result<(), ResolveWarning> {
+    Ok(parse_descriptor(map)?.validate(value)?)
+}
 
-    let min = min.as_u64().unwrap();
-    let max = max.as_u64().unwrap();
-    if unscaled_value < min || unscaled_value > max {
-        return Some(format!(
-            "Value out of range: {min} ≤ {unscaled_value} ≤ {max}"
-        ));
-    }
-    None
+fn parse_descriptor(map: &Map<String, Value>) -> io::Result<Descriptor> {
+    serde_json::from_value(Value::Object(map.clone())).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Invalid descriptor: {e}"),
+        )
+    })
 }
 
 fn resolve_checksum16<T: Read + Seek>(
-    endian: Endian,
+    platform: &Platform,
     rom: &mut T,
     value: &Value,
-    offset: u64,
-) -> io::Result<Value> {
+) -> io::Result<Vec<ResolvedChecksum>> {
     // go over the checksum16 array and verify the checksum
-    let mut checksum_result: Vec<Value> = Vec::new();
+    let mut checksum_result = Vec::new();
     for checksum in value.as_array().unwrap() {
         let checksum16: Checksum16 = serde_json::from_value(checksum.clone())?;
-        let checksum_failure = verify_checksum16(rom, &checksum16, endian, offset)?;
-        let mut map = Map::new();
-        if let Some(label) = checksum.get("label") {
-            map.insert("label".to_string(), label.clone());
-        }
-        if let Some(checksum_failure) = checksum_failure {
-            map.insert("value".to_string(), Value::String("mismatch".to_string()));
-            map.insert(
-                "checksum_mismatch_expected".to_string(),
-                Value::Number(checksum_failure.expected.into()),
-            );
-            map.insert(
-                "checksum_mismatch_calculated".to_string(),
-                Value::Number(checksum_failure.calculated.into()),
-            );
-            checksum_result.push(Value::Object(map));
-        } else {
-            map.insert("value".to_string(), Value::String("valid".to_string()));
-            checksum_result.push(Value::Object(map));
-        }
+        let checksum_failure = verify_checksum16(rom, &checksum16, platform)?;
+        checksum_result.push(ResolvedChecksum::new(
+            checksum16.label.clone(),
+            checksum_failure,
+        ));
     }
-    Ok(Value::Array(checksum_result))
+    Ok(checksum_result)
 }
 
-fn resolve_checksum8<T: Read + Seek>(rom: &mut T, value: &Value) -> io::Result<Value> {
-    // go over the checksum16 array and verify the checksum
-    let mut checksum_result: Vec<Value> = Vec::new();
+fn resolve_checksum8<T: Read + Seek>(
+    platform: &Platform,
+    rom: &mut T,
+    value: &Value,
+) -> io::Result<Vec<ResolvedChecksum>> {
+    // go over the checksum8 array and verify the checksum
+    let mut checksum_result = Vec::new();
     for checksum in value.as_array().unwrap() {
         let checksum8: Checksum8 = serde_json::from_value(checksum.clone())?;
-        let checksum_failure = verify_checksum8(rom, &checksum8)?;
-        let mut map = Map::new();
-        if let Some(label) = checksum.get("label") {
-            map.insert("label".to_string(), label.clone());
-        }
-        if let Some(checksum_failure) = checksum_failure {
-            map.insert("value".to_string(), Value::String("mismatch".to_string()));
-            map.insert(
-                "checksum_mismatch_expected".to_string(),
-                Value::Number(checksum_failure.expected.into()),
-            );
-            map.insert(
-                "checksum_mismatch_calculated".to_string(),
-                Value::Number(checksum_failure.calculated.into()),
-            );
-            checksum_result.push(Value::Object(map));
-        } else {
-            map.insert("value".to_string(), Value::String("valid".to_string()));
-            checksum_result.push(Value::Object(map));
-        }
+        let checksum_failure = verify_checksum8(rom, &checksum8, platform)?;
+        checksum_result.push(ResolvedChecksum::new(
+            Some(checksum8.label.clone()),
+            checksum_failure,
+        ));
     }
-    Ok(Value::Array(checksum_result))
+    Ok(checksum_result)
 }
 
-/// Resolve a descriptor to its display value plus the raw numeric value that
-/// range validation should be applied to.
+/// Resolve a descriptor to its display value, with `scale`, `offset` and
+/// `special_values` applied and an `enum` index looked up in its `values`.
 ///
-/// For most numeric encodings the raw value matches the display value, but for
-/// `enum` the display value is a string label while the range (`min`/`max`)
-/// applies to the underlying index. Encodings that do not produce a number
-/// return `None` as the raw value and are never range-checked.
-fn resolve_value<T: Read + Seek, U: GlobalSettings>(
-    rom: &mut T,
+/// Values that can not be resolved, like values outside the NVRAM or an `enum`
+/// index without a value, are returned as a [`ResolveWarning`]. The value is not
+/// validated against `min`, `max` and `multiple_of`, see [`Descriptor::validate`].
+pub(crate) fn resolve_value<U: GlobalSettings>(
+    memory: &mut Memory,
     descriptor: &Map<String, Value>,
     encoding: Encoding,
     global_settings: &U,
     platform: &Platform,
-) -> io::Result<(Value, Option<u64>)> {
-    // we only have access to nvram files and cannot access the RAM.
-    let nvram_layout = platform.layout(MemoryLayoutType::NVRam);
-    let nibble = nvram_layout.nibble();
-    let endian = platform.endian;
+) -> Result<NvValue, ResolveWarning> {
+    let nibble = descriptor_nibble(descriptor, platform)?;
+    let endian = descriptor_endian(descriptor, platform)?;
+    let mask = descriptor.get("mask").map(json_hex_or_int).transpose()?;
     let length = descriptor
         .get("length")
         .map_or(DEFAULT_LENGTH, |v| v.as_u64().unwrap() as usize);
-    // The raw numeric value the descriptor's min/max range applies to, if any.
-    let mut range_value: Option<u64> = None;
     let value = match encoding {
         Encoding::Int => {
             let scale = descriptor
@@ -270,208 +694,439 @@ fn resolve_value<T: Read + Seek, U: GlobalSettings>(
                 .and_then(|s| s.as_number())
                 .cloned()
                 .unwrap_or(Number::from(DEFAULT_SCALE));
-            let location = location_in_nvram_file(nvram_layout, descriptor, length)?;
-            let value = read_int(rom, endian, nibble, location, &scale)?;
-            let display = value as i128 + value_offset(descriptor);
+            let (mut rom, location) = located_stream(memory, platform, descriptor, length)?;
+            let value = apply_scale(&scale, read_int(&mut rom, endian, nibble, location, mask)?)?;
+            let display = apply_offset(value, value_offset(descriptor))?;
             if let Some(label) = special_value(descriptor, display) {
-                return Ok((Value::String(label), None));
+                return Ok(NvValue::Text(label));
             }
-            range_value = Some(value);
-            Value::Number(number_from_i128(display))
+            NvValue::Integer(display)
         }
         Encoding::Enum => {
-            let location = location_in_nvram_file(nvram_layout, descriptor, length)?;
-            let index =
-                read_int(rom, endian, nibble, location, &Number::from(DEFAULT_SCALE))? as usize;
-            let values = descriptor.get("values").unwrap().as_array().unwrap();
+            let (mut rom, location) = located_stream(memory, platform, descriptor, length)?;
+            let index = read_int(&mut rom, endian, nibble, location, mask)? as usize;
+            let values = descriptor_values(descriptor, global_settings)?;
             match values.get(index) {
-                Some(enum_value) => {
-                    // The range (min/max) constrains the raw index, not the label.
-                    range_value = Some(index as u64);
-                    enum_value.clone()
-                }
+                Some(enum_value) => NvValue::Enum {
+                    index,
+                    label: Some(enum_value.clone()),
+                },
                 None => {
-                    return Err(io::Error::new(
-                        io::ErrorKind::InvalidData,
-                        format!(
-                            "Index {} out of bounds for enum with {} values",
-                            index,
-                            values.len()
-                        ),
-                    ));
+                    return Err(ResolveWarning::UnknownEnumIndex {
+                        index,
+                        count: values.len(),
+                    });
                 }
             }
         }
         Encoding::Bcd => {
-            let location = location_in_nvram_file(nvram_layout, descriptor, length)?;
+            let (mut rom, location) = located_stream(memory, platform, descriptor, length)?;
             let scale = descriptor
                 .get("scale")
                 .and_then(|s| s.as_number())
                 .cloned()
                 .unwrap_or(Number::from(DEFAULT_SCALE));
-            // how can we avoid the clone() here?
-            let nibble: Nibble = descriptor
-                .get("nibble")
-                .map(|n| serde_json::from_value(n.clone()).unwrap())
-                .unwrap_or(nibble);
-
-            let value = read_bcd(rom, location, nibble, &scale, endian)?;
-            let display = value as i128 + value_offset(descriptor);
+            let blank = platform.bcd_blank();
+            let value = read_bcd(&mut rom, location, nibble, endian, mask, blank)?;
+            let value = apply_scale(&scale, value)?;
+            let display = apply_offset(value, value_offset(descriptor))?;
             if let Some(label) = special_value(descriptor, display) {
-                return Ok((Value::String(label), None));
+                return Ok(NvValue::Text(label));
             }
-            range_value = Some(value);
-            Value::Number(number_from_i128(display))
+            NvValue::Integer(display)
         }
         Encoding::Ch => {
-            let location = location_in_nvram_file(nvram_layout, descriptor, length)?;
-            let mask = descriptor.get("mask").map(json_hex_or_int).transpose()?;
-            let nibble = descriptor
-                .get("nibble")
-                .map(|n| serde_json::from_value(n.clone()).unwrap())
-                .unwrap_or(nibble);
+            let (mut rom, location) = located_stream(memory, platform, descriptor, length)?;
             let null: Option<Null> = descriptor
                 .get("null")
                 .map(|n| serde_json::from_value(n.clone()).unwrap());
-            let value = read_ch(
-                rom,
-                location,
-                mask,
-                global_settings.char_map(),
-                nibble,
-                null,
-            )?;
-            Value::String(value)
+            let charset = descriptor_charset(descriptor, global_settings, platform)?;
+            read_ch(&mut rom, location, mask, &charset, nibble, null)?
         }
         Encoding::WpcRtc => {
-            let start = start_in_nvram_file(nvram_layout, descriptor)?;
-            let value = read_wpc_rtc(rom, start, length)?;
-            Value::String(value)
-        }
-        Encoding::Bits => {
-            let value = "Bits encoding not implemented".to_string();
-            Value::String(value)
+            let (mut rom, location) = located_stream(memory, platform, descriptor, length)?;
+            let value = read_wpc_rtc(&mut rom, &location)?;
+            NvValue::Timestamp(value)
         }
+        Encoding::Bits => return Err(ResolveWarning::Unsupported(encoding)),
         Encoding::Raw => {
-            let start = start_in_nvram_file(nvram_layout, descriptor)?;
-            let mut buff = vec![0; length];
-            read_exact_at(rom, start, &mut buff)?;
-            Value::Array(buff.iter().map(|b| Value::Number((*b).into())).collect())
+            let (mut rom, location) = located_stream(memory, platform, descriptor, length)?;
+            NvValue::Bytes(read_location(&mut rom, &location)?)
         }
         Encoding::Dipsw => {
-            let offsets = descriptor
-                .get("offsets")
-                .unwrap()
-                .as_array()
-                .unwrap()
-                .iter()
-                .map(json_hex_or_int)
-                .collect::<io::Result<Vec<_>>>()?;
-
             let mut dips = Vec::new();
-            for offset in offsets {
-                let dip_on = dips::get_dip_switch(rom, offset as usize)?;
+            for offset in dipsw_offsets(descriptor)? {
+                let dip_on = dips::get_dip_switch(&mut memory.nvram(), offset)?;
                 dips.push(dip_on);
             }
+            if !descriptor.contains_key("values") {
+                return Ok(NvValue::Flags(dips));
+            }
             // convert the bits to a number, always msb first
             let mut value = 0;
             for dip in dips.iter() {
                 value = (value << 1) | if *dip { 1 } else { 0 };
             }
 
-            let values = descriptor
-                .get("values")
-                .expect("Missing values for dip switch");
             let index = value as usize;
-            match values {
-                Value::Array(array) => array.get(index).unwrap_or(&Value::Null).clone(),
-                Value::String(value_reference) => {
-                    match global_settings.value(value_reference, index) {
-                        Some(value) => Value::String(value),
-                        None => Value::Null,
-                    }
-                }
-                _ => {
-                    panic!("Unexpected dip switch values type: {values:?}");
+            let label = descriptor_values(descriptor, global_settings)?
+                .get(index)
+                .cloned();
+            NvValue::Enum { index, label }
+        }
+        Encoding::Bool => {
+            let (mut rom, location) = located_stream(memory, platform, descriptor, length)?;
+            let invert = descriptor
+                .get("invert")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(DEFAULT_INVERT);
+            let bool_value = read_bool(&mut rom, nibble, endian, location, mask, invert)?;
+            NvValue::Bool(bool_value)
+        }
+    };
+    Ok(value)
+}
+
+/// Write a value to the location of a descriptor in the .nv file, the counterpart of
+/// [`resolve_value`].
+///
+/// Checksums are not updated.
+pub(crate) fn write_value<T: Read + Write + Seek, U: GlobalSettings>(
+    nvram_file: &mut T,
+    descriptor: &Map<String, Value>,
+    value: &NvValue,
+    global_settings: &U,
+    platform: &Platform,
+) -> io::Result<()> {
+    let encoding: Encoding =
+        serde_json::from_value(descriptor.get("encoding").cloned().unwrap_or(Value::Null))?;
+    if encoding == Encoding::Dipsw {
+        return write_dipsw(nvram_file, descriptor, value, global_settings);
+    }
+    let nibble = descriptor_nibble(descriptor, platform)?;
+    let endian = descriptor_endian(descriptor, platform)?;
+    let mask = descriptor.get("mask").map(json_hex_or_int).transpose()?;
+    let length = descriptor
+        .get("length")
+        .map_or(DEFAULT_LENGTH, |v| v.as_u64().unwrap() as usize);
+    let location = match locate(platform, descriptor_location(descriptor, length)?)? {
+        LocateResult::Located(location) => location,
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Value is stored outside the NVRAM",
+            ));
+        }
+    };
+    match (encoding, value) {
+        (Encoding::Int | Encoding::Bcd, value) => {
+            let display = match value {
+                NvValue::Integer(display) => {
+                    validate_write(descriptor, value)?;
+                    *display
                 }
+                NvValue::Text(label) => special_value_key(descriptor, label)
+                    .ok_or_else(|| invalid_value(encoding, value))?,
+                _ => return Err(invalid_value(encoding, value)),
+            };
+            let raw = unscale(descriptor, display)?;
+            if encoding == Encoding::Int {
+                return write_int(nvram_file, endian, nibble, location, mask, raw);
             }
+            write_bcd(nvram_file, location, nibble, endian, mask, raw)
         }
-        Encoding::Bool => {
-            let location = location_in_nvram_file(nvram_layout, descriptor, length)?;
+        (Encoding::Enum, value) => {
+            let index = enum_index(Encoding::Enum, descriptor, global_settings, value)?;
+            validate_write(descriptor, &NvValue::Enum { index, label: None })?;
+            write_int(nvram_file, endian, nibble, location, mask, index as u64)
+        }
+        (Encoding::Bool, NvValue::Bool(bool_value)) => {
             let invert = descriptor
                 .get("invert")
                 .and_then(|v| v.as_bool())
                 .unwrap_or(DEFAULT_INVERT);
-            let bool_value = read_bool(rom, nibble, endian, location, invert)?;
-            Value::Bool(bool_value)
+            // A set flag sets all bits of its mask, it reads back as `value & mask != 0`.
+            let raw = if *bool_value != invert {
+                mask.unwrap_or(1)
+            } else {
+                0
+            };
+            write_int(nvram_file, endian, nibble, location, mask, raw)
+        }
+        (Encoding::Ch, NvValue::Text(string)) => {
+            let null: Option<Null> = descriptor
+                .get("null")
+                .map(|n| serde_json::from_value(n.clone()))
+                .transpose()?;
+            let charset = descriptor_charset(descriptor, global_settings, platform)?;
+            write_ch(nvram_file, location, string, mask, &charset, nibble, null)
+        }
+        (Encoding::Ch, NvValue::UndecodedText { raw, .. }) => {
+            let null: Option<Null> = descriptor
+                .get("null")
+                .map(|n| serde_json::from_value(n.clone()))
+                .transpose()?;
+            let charset = descriptor_charset(descriptor, global_settings, platform)?;
+            write_ch_bytes(nvram_file, location, raw, mask, &charset, nibble, null)
+        }
+        (Encoding::WpcRtc, NvValue::Timestamp(timestamp)) => {
+            write_wpc_rtc(nvram_file, &location, timestamp)
+        }
+        (Encoding::Raw, NvValue::Bytes(bytes)) => write_location(nvram_file, &location, bytes),
+        (Encoding::Bool | Encoding::Ch | Encoding::WpcRtc | Encoding::Raw, value) => {
+            Err(invalid_value(encoding, value))
+        }
+        (encoding, _) => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("Writing {encoding} values is not supported"),
+        )),
+    }
+}
+
+/// Set the switches of a `dipsw` descriptor, the counterpart of reading it in
+/// [`resolve_value`].
+///
+/// The index of the value is written to the switches most significant bit first. Without
+/// `values` the switches are set from [`NvValue::Flags`].
+fn write_dipsw<T: Read + Write + Seek, U: GlobalSettings>(
+    nvram_file: &mut T,
+    descriptor: &Map<String, Value>,
+    value: &NvValue,
+    global_settings: &U,
+) -> io::Result<()> {
+    let offsets = dipsw_offsets(descriptor)?;
+    let flags = match value {
+        NvValue::Flags(flags) if flags.len() == offsets.len() => flags.clone(),
+        NvValue::Flags(_) => return Err(invalid_value(Encoding::Dipsw, value)),
+        _ => {
+            let index = enum_index(Encoding::Dipsw, descriptor, global_settings, value)?;
+            validate_write(descriptor, &NvValue::Enum { index, label: None })?;
+            if offsets.len() < usize::BITS as usize && index >> offsets.len() != 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "Index {index} does not fit in {} dip switches",
+                        offsets.len()
+                    ),
+                ));
+            }
+            (0..offsets.len())
+                .rev()
+                .map(|bit| index.checked_shr(bit as u32).unwrap_or(0) & 1 == 1)
+                .collect()
         }
     };
-    Ok((value, range_value))
+    for (offset, on) in offsets.into_iter().zip(flags) {
+        dips::set_dip_switch(nvram_file, offset, on)?;
+    }
+    Ok(())
+}
+
+/// The switch numbers of a `dipsw` descriptor, starting at 1, most significant first
+fn dipsw_offsets(descriptor: &Map<String, Value>) -> io::Result<Vec<usize>> {
+    descriptor
+        .get("offsets")
+        .and_then(Value::as_array)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Missing offsets for dip switches",
+            )
+        })?
+        .iter()
+        .map(|offset| json_hex_or_int(offset).map(|offset| offset as usize))
+        .collect()
+}
+
+/// [`validate_value`] for a value that is about to be written
+fn validate_write(descriptor: &Map<String, Value>, value: &NvValue) -> io::Result<()> {
+    parse_descriptor(descriptor)?
+        .validate(value)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error.to_string()))
 }
 
-/// Resolve the location of a value in the NVRAM file from a descriptor.
+fn invalid_value(encoding: Encoding, value: &NvValue) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("Can not write '{value}' as {encoding}"),
+    )
+}
+
+/// The displayed integer for a `special_values` label, the reverse of [`special_value`].
+fn special_value_key(descriptor: &Map<String, Value>, label: &str) -> Option<i128> {
+    descriptor
+        .get("special_values")?
+        .as_object()?
+        .iter()
+        .find(|(_, value)| value.as_str() == Some(label))
+        .and_then(|(key, _)| key.parse().ok())
+}
+
+/// Undo the `offset` and `scale` of a displayed integer, the stored value.
+fn unscale(descriptor: &Map<String, Value>, display: i128) -> io::Result<u64> {
+    let scale = descriptor
+        .get("scale")
+        .and_then(|s| s.as_number())
+        .cloned()
+        .unwrap_or(Number::from(DEFAULT_SCALE));
+    let scaled = display.checked_sub(i128::from(value_offset(descriptor)));
+    match scaled {
+        Some(scaled) => remove_scale(&scale, scaled)?,
+        None => None,
+    }
+    .ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Value {display} can not be stored with scale {scale}"),
+        )
+    })
+}
+
+/// The `values` of a descriptor, either listed or a reference to a list in
+/// `_metadata.values`.
+pub(crate) fn descriptor_values<U: GlobalSettings>(
+    descriptor: &Map<String, Value>,
+    global_settings: &U,
+) -> io::Result<Vec<Value>> {
+    match descriptor.get("values") {
+        Some(Value::Array(values)) => Ok(values.clone()),
+        Some(Value::String(reference)) => {
+            let values: Vec<Value> = (0..)
+                .map_while(|index| global_settings.value(reference, index))
+                .map(Value::String)
+                .collect();
+            if values.is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unknown values reference '{reference}'"),
+                ));
+            }
+            Ok(values)
+        }
+        _ => Err(io::Error::new(io::ErrorKind::InvalidData, "Missing values")),
+    }
+}
+
+/// The index of an `enum` value, given either as index or as one of its `values`.
+fn enum_index<U: GlobalSettings>(
+    encoding: Encoding,
+    descriptor: &Map<String, Value>,
+    global_settings: &U,
+    value: &NvValue,
+) -> io::Result<usize> {
+    let values = descriptor_values(descriptor, global_settings)?;
+    let index = match value {
+        NvValue::Enum { index, .. } => Some(*index),
+        NvValue::Integer(index) => usize::try_from(*index).ok(),
+        NvValue::Text(label) => values.iter().position(|v| v.as_str() == Some(label)),
+        _ => None,
+    };
+    index
+        .filter(|index| *index < values.len())
+        .ok_or_else(|| invalid_value(encoding, value))
+}
+
+/// Resolve the location of a value from a descriptor, together with the stream to read it from.
 ///
 /// A descriptor either has a single `start` address (a contiguous run of
 /// `length` bytes) or an `offsets` array listing the address of each byte
 /// (used by platforms that map 8-bit NVRAM on a wider bus, so the bytes are
 /// not adjacent in the file). Both forms are CPU addresses and are translated
-/// to file offsets by subtracting the NVRAM layout base address.
-fn location_in_nvram_file(
-    nvram_layout: &MemoryLayout,
+/// using the platform memory layout, to the .nv file or to the memory image.
+fn located_stream<'m>(
+    memory: &'m mut Memory,
+    platform: &Platform,
     descriptor: &Map<String, Value>,
     length: usize,
-) -> io::Result<Location> {
-    match descriptor.get("offsets") {
+) -> Result<(&'m mut dyn ReadSeek, Location), ResolveWarning> {
+    let location = descriptor_location(descriptor, length)?;
+    stream_for(memory, platform, location)
+}
+
+/// The CPU location of a descriptor, see [`located_stream`].
+fn descriptor_location(descriptor: &Map<String, Value>, length: usize) -> io::Result<Location> {
+    let location = match descriptor.get("offsets") {
         Some(offsets) => {
-            let base: u64 = (&nvram_layout.address).into();
             let offsets = offsets
                 .as_array()
                 .unwrap()
                 .iter()
-                .map(|o| {
-                    let address = json_hex_or_int(o)?;
-                    if address < base {
-                        Err(io::Error::new(
-                            io::ErrorKind::InvalidData,
-                            "Value is stored outside the NVRAM",
-                        ))
-                    } else {
-                        Ok(address - base)
-                    }
-                })
+                .map(json_hex_or_int)
                 .collect::<io::Result<Vec<u64>>>()?;
-            Ok(Location::Scattered { offsets })
-        }
-        None => {
-            let start = start_in_nvram_file(nvram_layout, descriptor)?;
-            Ok(Location::Continuous { start, length })
+            Location::Scattered { offsets }
         }
-    }
+        None => Location::Continuous {
+            start: start_address(descriptor)?,
+            length,
+        },
+    };
+    Ok(location)
 }
 
-fn start_in_nvram_file(
-    nvram_layout: &MemoryLayout,
-    descriptor: &Map<String, Value>,
-) -> io::Result<u64> {
-    let start_native = descriptor.get("start").map(json_hex_or_int).transpose()?;
-    if let Some(s) = start_native {
-        let offset: u64 = (&nvram_layout.address).into();
-        if s < offset {
-            Err(io::Error::new(
+fn start_address(descriptor: &Map<String, Value>) -> io::Result<u64> {
+    descriptor
+        .get("start")
+        .map(json_hex_or_int)
+        .transpose()?
+        .ok_or_else(|| {
+            io::Error::new(
                 io::ErrorKind::InvalidData,
-                "Value is stored outside the NVRAM",
-            ))
-        } else {
-            Ok(s - offset)
-        }
-    } else {
-        Err(io::Error::new(
-            io::ErrorKind::InvalidData,
-            "Missing start value for NVRAM encoding",
-        ))
+                "Missing start value for NVRAM encoding",
+            )
+        })
+}
+
+fn stream_for<'m>(
+    memory: &'m mut Memory,
+    platform: &Platform,
+    location: Location,
+) -> Result<(&'m mut dyn ReadSeek, Location), ResolveWarning> {
+    memory
+        .stream(locate(platform, location)?)
+        .ok_or(ResolveWarning::OutsideNvram)
+}
+
+/// The `nibble` of a descriptor, or the platform nibble for its first address.
+fn descriptor_nibble(descriptor: &Map<String, Value>, platform: &Platform) -> io::Result<Nibble> {
+    match descriptor.get("nibble") {
+        Some(nibble) => Ok(serde_json::from_value(nibble.clone())?),
+        None => Ok(first_address(descriptor)?
+            .map(|address| platform.nibble(address))
+            .unwrap_or(Nibble::Both)),
     }
 }
 
+/// The `endian` of a descriptor, or the platform endian.
+fn descriptor_endian(descriptor: &Map<String, Value>, platform: &Platform) -> io::Result<Endian> {
+    match descriptor.get("endian") {
+        Some(endian) => Ok(serde_json::from_value(endian.clone())?),
+        None => Ok(platform.endian),
+    }
+}
+
+/// The characters of a `ch` descriptor, the descriptor `period_flag` overrides the one of
+/// the platform.
+fn descriptor_charset<U: GlobalSettings>(
+    descriptor: &Map<String, Value>,
+    global_settings: &U,
+    platform: &Platform,
+) -> io::Result<Charset> {
+    let period_flag = match descriptor.get("period_flag") {
+        Some(period_flag) => serde_json::from_value(period_flag.clone())?,
+        None => platform.period_flag.unwrap_or(false),
+    };
+    Ok(Charset::new(global_settings.char_map(), period_flag))
+}
+
+/// The first CPU address a descriptor refers to, used to look up its memory region.
+fn first_address(descriptor: &Map<String, Value>) -> io::Result<Option<u64>> {
+    let first = match descriptor.get("offsets") {
+        Some(offsets) => offsets.as_array().and_then(|o| o.first()),
+        None => descriptor.get("start"),
+    };
+    first.map(json_hex_or_int).transpose()
+}
+
 fn json_hex_or_int(s: &Value) -> io::Result<u64> {
     match s {
         // TODO deduplicate
@@ -495,42 +1150,12 @@ fn json_hex_or_int(s: &Value) -> io::Result<u64> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util;
+    use crate::value::Timestamp;
     use pretty_assertions::assert_eq;
     use std::path::PathBuf;
     use testdir::testdir;
 
-    fn range_map() -> Map<String, Value> {
-        let mut map = Map::new();
-        map.insert("min".to_string(), Value::from(0));
-        map.insert("max".to_string(), Value::from(31));
-        map
-    }
-
-    #[test]
-    fn test_validate_range_in_range() {
-        assert_eq!(validate_range(&range_map(), Some(15)), None);
-    }
-
-    #[test]
-    fn test_validate_range_out_of_range() {
-        assert_eq!(
-            validate_range(&range_map(), Some(255)),
-            Some("Value out of range: 0 ≤ 255 ≤ 31".to_string())
-        );
-    }
-
-    #[test]
-    fn test_validate_range_no_raw_value_is_skipped() {
-        // encodings that do not produce a number pass None and are not checked
-        assert_eq!(validate_range(&range_map(), None), None);
-    }
-
-    #[test]
-    fn test_validate_range_no_bounds() {
-        // a descriptor without min/max is never range-checked
-        assert_eq!(validate_range(&Map::new(), Some(255)), None);
-    }
-
     #[test]
     fn test_value_offset() {
         assert_eq!(value_offset(&Map::new()), 0);
@@ -555,11 +1180,350 @@ mod tests {
         assert_eq!(special_value(&Map::new(), 0), None);
     }
 
+    fn resolve_map(map: &Value, nvram: Vec<u8>, selectors: &[&str]) -> io::Result<ResolvedNode> {
+        let global_settings = test_util::global_settings();
+        let platform = test_util::nvram_platform();
+        let selectors: Vec<Vec<String>> = selectors.iter().map(|s| parse_selector(s)).collect();
+        let selection = Selection::Paths(selectors.iter().map(Vec::as_slice).collect());
+        let mut nvram = io::Cursor::new(nvram);
+        let mut memory = Memory::new(&mut nvram, None);
+        Ok(
+            resolve_recursive(map, &global_settings, &platform, &mut memory, &selection)?
+                .unwrap_or(ResolvedNode::Section(BTreeMap::new())),
+        )
+    }
+
+    #[test]
+    fn test_resolve_selected() -> io::Result<()> {
+        let map = serde_json::json!({
+            "_fileformat": 0.8,
+            "adjustments": {
+                "Standard": {
+                    "01": {"label": "Balls Per Game", "encoding": "int", "start": 0},
+                    "02": {"label": "Free Play", "encoding": "bool", "start": 1}
+                },
+                "Feature": {
+                    "01": {"label": "Free Play", "encoding": "unknown", "start": 2}
+                }
+            },
+            "game_state": {
+                "credits": {"label": "Credits", "encoding": "int", "start": 3},
+                "tilted": {"label": "Tilted", "encoding": "bool", "start": 4}
+            },
+            "high_scores": [
+                {"label": "Grand Champion", "score": {"encoding": "bcd", "start": 5, "length": 2}},
+                {"label": "First Place", "score": {"encoding": "bcd", "start": 7, "length": 2}}
+            ]
+        });
+        let nvram = vec![3, 1, 0, 7, 0, 0x12, 0x34, 0x56, 0x78];
+        let resolved = resolve_map(
+            &map,
+            nvram,
+            &[
+                "adjustments/*/Free Play",
+                "game_state.credits",
+                "high_scores/1",
+            ],
+        )?;
+        // an invalid descriptor is recorded on the field
+        let feature_free_play = resolved
+            .get("adjustments")
+            .and_then(|a| a.get("Feature"))
+            .and_then(|f| f.get("01"))
+            .and_then(|e| e.as_entry())
+            .expect("Feature Free Play entry");
+        assert!(matches!(
+            feature_free_play.warning,
+            Some(ResolveWarning::DecodeError(_))
+        ));
+        let mut json = serde_json::to_value(&resolved)?;
+        json["adjustments"]
+            .as_object_mut()
+            .unwrap()
+            .remove("Feature");
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "adjustments": {
+                    "Standard": {
+                        "02": {"label": "Free Play", "value": true}
+                    }
+                },
+                "game_state": {
+                    "credits": {"label": "Credits", "value": 7}
+                },
+                "high_scores": [
+                    null,
+                    {"label": "First Place", "score": {"value": 5678}}
+                ]
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_selector() {
+        assert_eq!(
+            parse_selector("adjustments/*/Max E.B. Count"),
+            vec!["adjustments", "*", "Max E.B. Count"]
+        );
+        assert_eq!(
+            parse_selector("game_state.credits"),
+            vec!["game_state", "credits"]
+        );
+    }
+
     #[test]
-    fn test_number_from_i128() {
-        assert_eq!(number_from_i128(255), Number::from(255u64));
-        assert_eq!(number_from_i128(0), Number::from(0u64));
-        assert_eq!(number_from_i128(-3), Number::from(-3i64));
+    fn test_serialize_resolved_entry() -> io::Result<()> {
+        let entry = |value| ResolvedEntry {
+            label: Some("Credits".to_string()),
+            raw: None,
+            units: None,
+            value: Some(value),
+            warning: None,
+        };
+        assert_eq!(
+            serde_json::to_value(entry(NvValue::Integer(-3)))?,
+            serde_json::json!({"label": "Credits", "value": -3})
+        );
+        assert_eq!(
+            serde_json::to_value(entry(NvValue::Enum {
+                index: 1,
+                label: Some(Value::from("On"))
+            }))?,
+            serde_json::json!({"label": "Credits", "value": "On"})
+        );
+        assert_eq!(
+            serde_json::to_value(entry(NvValue::Enum {
+                index: 7,
+                label: None
+            }))?,
+            serde_json::json!({"label": "Credits", "value": null})
+        );
+        assert_eq!(
+            serde_json::to_value(entry(NvValue::Bytes(vec![1, 2])))?,
+            serde_json::json!({"label": "Credits", "value": [1, 2]})
+        );
+        let failed = ResolvedEntry {
+            label: None,
+            raw: None,
+            units: None,
+            value: None,
+            warning: Some(ResolveWarning::DecodeError("boom".to_string())),
+        };
+        assert_eq!(
+            serde_json::to_value(failed)?,
+            serde_json::json!({"warning": "Failed to resolve: boom"})
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_invalid_bcd() -> io::Result<()> {
+        let map = serde_json::json!({
+            "high_scores": [
+                {"label": "Grand Champion", "score": {"encoding": "bcd", "start": 0, "length": 2}},
+                {"label": "First Place", "score": {"encoding": "bcd", "start": 2, "length": 2}}
+            ]
+        });
+        let nvram = vec![0xF1, 0x23, 0x1B, 0x23];
+        let resolved = resolve_map(&map, nvram, &["high_scores"])?;
+        let score = |index: usize| {
+            resolved
+                .get("high_scores")
+                .and_then(ResolvedNode::as_list)
+                .and_then(|scores| scores.get(index))
+                .and_then(|score| score.get("score"))
+                .and_then(ResolvedNode::as_entry)
+                .cloned()
+        };
+        let blank = score(0).unwrap();
+        assert_eq!(Some(NvValue::Integer(123)), blank.value);
+        assert_eq!(None, blank.warning);
+        let corrupted = score(1).unwrap();
+        assert_eq!(None, corrupted.value);
+        assert_eq!(
+            Some("Failed to resolve: Invalid BCD digit 0xB".to_string()),
+            corrupted.warning.map(|warning| warning.to_string())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_invalid_descriptor() -> io::Result<()> {
+        let map = serde_json::json!({
+            "volume": {"label": "Volume", "encoding": "int", "start": 0, "max": -1}
+        });
+        let resolved = resolve_map(&map, vec![0x08], &["volume"])?;
+        let volume = resolved.get("volume").and_then(ResolvedNode::as_entry);
+        assert_eq!(
+            Some(
+                "Failed to resolve: Invalid descriptor: invalid value: integer `-1`, expected u64"
+                    .to_string()
+            ),
+            volume.and_then(|entry| entry.warning.as_ref().map(ToString::to_string))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_undecoded_text() -> io::Result<()> {
+        let map = serde_json::json!({
+            "initials": {"label": "Initials", "encoding": "ch", "start": 0, "length": 3},
+            "message": {"label": "Message", "encoding": "ch", "start": 3, "length": 2, "period_flag": true}
+        });
+        let resolved = resolve_map(
+            &map,
+            vec![0x41, 0xFF, 0x43, 0xC4, 0x2E],
+            &["initials", "message"],
+        )?;
+        let initials = resolved.get("initials").and_then(ResolvedNode::as_entry);
+        assert_eq!(
+            Some(
+                serde_json::json!({"label": "Initials", "raw": [0x41, 0xFF, 0x43], "value": "A\u{FFFD}C"})
+            ),
+            initials.map(serde_json::to_value).transpose()?
+        );
+        let message = resolved.get("message").and_then(ResolvedNode::as_entry);
+        assert_eq!(
+            Some(serde_json::json!({"label": "Message", "value": "D.."})),
+            message.map(serde_json::to_value).transpose()?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_timestamp() -> io::Result<()> {
+        let map = serde_json::json!({
+            "last_played": {"encoding": "wpc_rtc", "start": 0, "length": 7},
+            "clock": {"encoding": "wpc_rtc", "offsets": [7, 9, 11, 13, 15, 8, 10]}
+        });
+        let nvram = vec![
+            0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x07, 0x10, 0xE8, 0x2A, 0x06, 0, 0x13, 0,
+            0x04,
+        ];
+        let resolved = resolve_map(&map, nvram, &["last_played", "clock"])?;
+        let last_played = resolved.get("last_played").and_then(ResolvedNode::as_entry);
+        assert_eq!(
+            Some(ResolveWarning::InvalidTimestamp),
+            last_played.and_then(|entry| entry.warning.clone())
+        );
+        let clock = resolved.get("clock").and_then(ResolvedNode::as_entry);
+        assert_eq!(
+            Some(NvValue::Timestamp(
+                Timestamp::new(2024, 6, 19, 16, 42).unwrap()
+            )),
+            clock.and_then(|entry| entry.value.clone())
+        );
+        assert_eq!(None, clock.and_then(|entry| entry.warning.clone()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_warnings() {
+        let entry = |label: &str, warning| {
+            ResolvedNode::Entry(ResolvedEntry {
+                label: Some(label.to_string()),
+                raw: None,
+                units: None,
+                value: None,
+                warning,
+            })
+        };
+        let out_of_range = ResolveWarning::Invalid(ValidationError::OutOfRange {
+            min: Some(0),
+            max: Some(31),
+            value: 255,
+        });
+        let resolved = ResolvedNvram {
+            sections: BTreeMap::from([
+                (
+                    "adjustments".to_string(),
+                    ResolvedNode::Section(BTreeMap::from([
+                        (
+                            "volume".to_string(),
+                            entry("Volume", Some(out_of_range.clone())),
+                        ),
+                        ("balls".to_string(), entry("Balls", None)),
+                    ])),
+                ),
+                (
+                    "high_scores".to_string(),
+                    ResolvedNode::List(vec![
+                        ResolvedNode::Section(BTreeMap::new()),
+                        ResolvedNode::Section(BTreeMap::from([(
+                            "score".to_string(),
+                            entry("Score", Some(ResolveWarning::OutsideNvram)),
+                        )])),
+                    ]),
+                ),
+            ]),
+        };
+        assert_eq!(
+            resolved.warnings(),
+            vec![
+                DescriptorWarning {
+                    path: "adjustments/volume".to_string(),
+                    label: Some("Volume".to_string()),
+                    warning: out_of_range.clone(),
+                },
+                DescriptorWarning {
+                    path: "high_scores/1/score".to_string(),
+                    label: Some("Score".to_string()),
+                    warning: ResolveWarning::OutsideNvram,
+                },
+            ]
+        );
+        // the paths select the descriptors in the map
+        let map = serde_json::json!({
+            "adjustments": {"volume": {"label": "Volume", "encoding": "int", "start": 0}},
+            "high_scores": [
+                {},
+                {"score": {"label": "Score", "encoding": "bcd", "start": 1}}
+            ]
+        });
+        for warning in resolved.warnings() {
+            let descriptor = find_descriptor(&map, &warning.path).unwrap();
+            assert_eq!(
+                warning.label.as_deref(),
+                descriptor.get("label").and_then(Value::as_str)
+            );
+        }
+        assert_eq!(out_of_range.to_string(), "Value out of range: 0 ≤ 255 ≤ 31");
+        assert_eq!(
+            ResolveWarning::UnknownEnumIndex {
+                index: 15,
+                count: 4
+            }
+            .to_string(),
+            "Failed to resolve: Index 15 out of bounds for enum with 4 values"
+        );
+    }
+
+    #[test]
+    fn test_serialize_resolved_checksum() -> io::Result<()> {
+        let valid = ResolvedChecksum::new::<u8>(Some("Adjustments".to_string()), None);
+        assert_eq!(
+            serde_json::to_value(valid)?,
+            serde_json::json!({"label": "Adjustments", "value": "valid"})
+        );
+        let mismatch = ResolvedChecksum::new(
+            None,
+            Some(ChecksumMismatch {
+                label: None,
+                calculated: 0x12u8,
+                expected: 0x34u8,
+            }),
+        );
+        assert_eq!(
+            serde_json::to_value(mismatch)?,
+            serde_json::json!({
+                "checksum_mismatch_calculated": 0x12,
+                "checksum_mismatch_expected": 0x34,
+                "value": "mismatch"
+            })
+        );
+        Ok(())
     }
 
     #[test]
@@ -583,24 +1547,30 @@ mod tests {
         // `scale` is deliberately not propagated; `value` is already post-
         // scaled by resolve_value, so exposing it would invite double-scaling
         // bugs in callers.
-        let resolved = resolve(Path::new("testdata/lotr.nv"))?.expect("lotr.nv should resolve");
+        let resolved =
+            resolve_typed(Path::new("testdata/lotr.nv"))?.expect("lotr.nv should resolve");
         let drc = resolved
             .get("mode_champions")
-            .and_then(|v| v.as_array())
+            .and_then(|v| v.as_list())
             .and_then(|a| {
                 a.iter().find(|e| {
-                    e.get("label") == Some(&Value::String("Destroy Ring Champion".to_string()))
+                    e.get("label")
+                        == Some(&ResolvedNode::Other(Value::from("Destroy Ring Champion")))
                 })
             })
             .expect("Destroy Ring Champion entry");
-        let score = drc.get("score").expect("score object");
+        let score = drc
+            .get("score")
+            .and_then(|v| v.as_entry())
+            .expect("score entry");
         assert_eq!(
-            score.get("units").and_then(|v| v.as_str()),
+            score.units.as_deref(),
             Some("seconds"),
             "units annotation should be on the resolved score"
         );
+        let score_json = serde_json::to_value(score)?;
         assert!(
-            score.get("scale").is_none(),
+            score_json.get("scale").is_none(),
             "scale should NOT be propagated - value is already post-scaled"
         );
         Ok(())
@@ -636,6 +1606,257 @@ mod tests {
         Ok(())
     }
 
+    /// Write a value to a copy of the nvram, check that it decodes to the same value and
+    /// return the written bytes.
+    fn round_trip<U: GlobalSettings>(
+        nvram: &[u8],
+        descriptor: &Map<String, Value>,
+        value: &NvValue,
+        global_settings: &U,
+        platform: &Platform,
+    ) -> io::Result<Vec<u8>> {
+        let mut written = io::Cursor::new(nvram.to_vec());
+        write_value(&mut written, descriptor, value, global_settings, platform)?;
+        let encoding = serde_json::from_value(descriptor["encoding"].clone()).unwrap();
+        let mut memory = Memory::new(&mut written, None);
+        let decoded = resolve_value(&mut memory, descriptor, encoding, global_settings, platform)
+            .unwrap_or_else(|e| panic!("{descriptor:?} can not be read after writing: {e}"));
+        assert_eq!(value, &decoded, "{descriptor:?}");
+        Ok(written.into_inner())
+    }
+
+    #[test]
+    fn test_write_value_round_trip() -> io::Result<()> {
+        let global_settings = test_util::global_settings();
+        let platform = Platform {
+            endian: Endian::Little,
+            ..test_util::nvram_platform()
+        };
+        let cases = [
+            (
+                serde_json::json!({"encoding": "int", "start": 0, "length": 2, "scale": 10, "offset": -5}),
+                NvValue::Integer(12_345),
+                vec![0xD3, 0x04],
+            ),
+            (
+                serde_json::json!({"encoding": "int", "offsets": [0, 2], "special_values": {"0": "OFF"}}),
+                NvValue::Text("OFF".to_string()),
+                vec![0x00, 0xFF, 0x00],
+            ),
+            (
+                serde_json::json!({"encoding": "bcd", "offsets": [0, 2], "scale": 10}),
+                NvValue::Integer(12_340),
+                vec![0x34, 0xFF, 0x12],
+            ),
+            (
+                serde_json::json!({"encoding": "bool", "offsets": [1], "invert": true}),
+                NvValue::Bool(true),
+                vec![0xFF, 0x00],
+            ),
+            (
+                serde_json::json!({"encoding": "enum", "start": 0, "values": ["Off", "On", "Auto"]}),
+                NvValue::Enum {
+                    index: 2,
+                    label: Some(Value::from("Auto")),
+                },
+                vec![0x02],
+            ),
+            (
+                serde_json::json!({"encoding": "raw", "offsets": [0, 2, 4]}),
+                NvValue::Bytes(vec![0x01, 0x02, 0x03]),
+                vec![0x01, 0xFF, 0x02, 0xFF, 0x03],
+            ),
+            (
+                serde_json::json!({"encoding": "ch", "offsets": [0, 2]}),
+                NvValue::Text("AB".to_string()),
+                vec![0x41, 0xFF, 0x42],
+            ),
+            (
+                serde_json::json!({"encoding": "int", "start": 0, "length": 2, "endian": "big"}),
+                NvValue::Integer(0x1234),
+                vec![0x12, 0x34],
+            ),
+            (
+                serde_json::json!({"encoding": "bool", "start": 0, "mask": "0x04"}),
+                NvValue::Bool(false),
+                vec![0xFB],
+            ),
+            (
+                serde_json::json!({"encoding": "enum", "start": 0, "mask": "0x0F", "values": ["Off", "On"]}),
+                NvValue::Enum {
+                    index: 1,
+                    label: Some(Value::from("On")),
+                },
+                vec![0xF1],
+            ),
+            (
+                serde_json::json!({"encoding": "bcd", "start": 0, "length": 2, "mask": "0x7F"}),
+                NvValue::Integer(1234),
+                vec![0xB4, 0x92],
+            ),
+            (
+                serde_json::json!({"encoding": "int", "start": 0, "length": 2, "nibble": "low"}),
+                NvValue::Integer(0x12),
+                vec![0x01, 0x02],
+            ),
+            (
+                serde_json::json!({"encoding": "wpc_rtc", "start": 0, "length": 7}),
+                NvValue::Timestamp(Timestamp::new(2024, 6, 19, 16, 42).unwrap()),
+                vec![0x07, 0xE8, 0x06, 0x13, 0x04, 0x10, 0x2A],
+            ),
+        ];
+        for (descriptor, value, expected) in cases {
+            let descriptor = descriptor.as_object().unwrap();
+            let written = round_trip(
+                &[0xFF; 0x10],
+                descriptor,
+                &value,
+                &global_settings,
+                &platform,
+            )
+            .unwrap_or_else(|e| panic!("{descriptor:?} can not be written: {e}"));
+            assert_eq!(&written[..expected.len()], expected, "{descriptor:?}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_value_validates() -> io::Result<()> {
+        let global_settings = test_util::global_settings();
+        let platform = test_util::nvram_platform();
+        let cases = [
+            (
+                serde_json::json!({"encoding": "int", "start": 0, "max": 31}),
+                NvValue::Integer(32),
+                "Value out of range: 32 ≤ 31",
+            ),
+            (
+                serde_json::json!({"encoding": "bcd", "start": 0, "scale": 0.5, "multiple_of": 4}),
+                NvValue::Integer(1),
+                "Value 2 is not a multiple of 4",
+            ),
+            (
+                serde_json::json!({"encoding": "enum", "start": 0, "values": ["A", "B", "C"], "min": 1}),
+                NvValue::Text("A".to_string()),
+                "Value out of range: 1 ≤ 0",
+            ),
+            (
+                serde_json::json!({"encoding": "dipsw", "offsets": [1, 2], "values": ["A", "B", "C", "D"], "max": 2}),
+                NvValue::Text("D".to_string()),
+                "Value out of range: 3 ≤ 2",
+            ),
+            (
+                serde_json::json!({"encoding": "int", "start": 0, "max": -1}),
+                NvValue::Integer(0),
+                "Invalid descriptor: invalid value: integer `-1`, expected u64",
+            ),
+        ];
+        for (descriptor, value, expected) in cases {
+            let mut cursor = io::Cursor::new(vec![0x01]);
+            let descriptor = descriptor.as_object().unwrap();
+            let result = write_value(&mut cursor, descriptor, &value, &global_settings, &platform);
+            assert_eq!(result.unwrap_err().to_string(), expected, "{descriptor:?}");
+            assert_eq!(cursor.into_inner(), vec![0x01], "{descriptor:?}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_enum_values_reference() -> io::Result<()> {
+        let global_settings: GlobalSettingsImpl = serde_json::from_value(serde_json::json!({
+            "_metadata": {
+                "platform": "test", "version": 1, "roms": ["test"],
+                "values": {"no_yes": ["No", "Yes"]}
+            }
+        }))?;
+        let platform = test_util::nvram_platform();
+        let descriptor = serde_json::json!({"encoding": "enum", "start": 0, "values": "no_yes"});
+        let descriptor = descriptor.as_object().unwrap();
+        let mut cursor = io::Cursor::new(vec![0x01]);
+        let mut memory = Memory::new(&mut cursor, None);
+        let value = resolve_value(
+            &mut memory,
+            descriptor,
+            Encoding::Enum,
+            &global_settings,
+            &platform,
+        )
+        .unwrap();
+        let yes = NvValue::Enum {
+            index: 1,
+            label: Some(Value::from("Yes")),
+        };
+        assert_eq!(yes, value);
+        let no = NvValue::Enum {
+            index: 0,
+            label: Some(Value::from("No")),
+        };
+        let written = round_trip(&[0x01], descriptor, &no, &global_settings, &platform)?;
+        assert_eq!(vec![0x00], written);
+
+        let unknown = serde_json::json!({"encoding": "enum", "start": 0, "values": "unknown"});
+        let mut memory = Memory::new(&mut cursor, None);
+        let result = resolve_value(
+            &mut memory,
+            unknown.as_object().unwrap(),
+            Encoding::Enum,
+            &global_settings,
+            &platform,
+        );
+        assert_eq!(
+            Some("Failed to resolve: Unknown values reference 'unknown'".to_string()),
+            result.err().map(|warning| warning.to_string())
+        );
+        Ok(())
+    }
+
+    /// Every value in the test nvrams that can be written decodes to the same value again
+    #[test]
+    fn test_write_value_round_trip_all() -> io::Result<()> {
+        let test_dir = testdir!();
+        let mut written = 0;
+        let mut failures = Vec::new();
+        for entry in std::fs::read_dir("testdata")? {
+            let nvram_path = entry?.path();
+            if nvram_path.extension().unwrap() != "nv" {
+                continue;
+            }
+            let path = path_for_test(&test_dir, &nvram_path)?;
+            let Some(map) = open_nvram::<Value>(&path)? else {
+                continue;
+            };
+            let global_settings: GlobalSettingsImpl = serde_json::from_value(map.clone())?;
+            let platform: Platform = read_platform(global_settings.platform())?;
+            let nvram = std::fs::read(&path)?;
+            let mut found = Vec::new();
+            descriptors(&map, "", &mut found);
+            for (descriptor_path, descriptor) in found {
+                let encoding: Encoding = serde_json::from_value(descriptor["encoding"].clone())?;
+                let mut cursor = io::Cursor::new(nvram.clone());
+                let mut memory = Memory::new(&mut cursor, None);
+                let resolved = resolve_value(
+                    &mut memory,
+                    descriptor,
+                    encoding,
+                    &global_settings,
+                    &platform,
+                );
+                // values with a warning are pinned by test_expected_warnings
+                if resolved_warning(descriptor, &resolved).is_some() {
+                    continue;
+                }
+                let value = resolved.unwrap();
+                match round_trip(&nvram, descriptor, &value, &global_settings, &platform) {
+                    Ok(_) => written += 1,
+                    Err(e) => failures.push(format!("{path:?} {descriptor_path}: {e}")),
+                }
+            }
+        }
+        assert_eq!(Vec::<String>::new(), failures);
+        assert!(written > 0, "No values written");
+        Ok(())
+    }
+
     #[test]
     fn test_resolve_all() -> io::Result<()> {
         // any nvram that contains - in the file name needs to be renamed first
@@ -677,36 +1898,6 @@ mod tests {
         Ok(())
     }
 
-    /// Warnings of this kind are tolerated wholesale by the ratchet: the value
-    /// is simply not part of the dumped NVRAM region, which is common and
-    /// expected, so we do not enumerate them in the expected-warnings file.
-    const OUTSIDE_NVRAM_WARNING: &str = "Value is stored outside the NVRAM";
-
-    fn collect_warnings(rom: &str, value: &Value, out: &mut Vec<String>) {
-        match value {
-            Value::Object(map) => {
-                if let Some(Value::String(warning)) = map.get("warning")
-                    && !warning.contains(OUTSIDE_NVRAM_WARNING)
-                {
-                    let label = map
-                        .get("label")
-                        .and_then(|l| l.as_str())
-                        .unwrap_or("(no label)");
-                    out.push(format!("{rom} | {label} | {warning}"));
-                }
-                for v in map.values() {
-                    collect_warnings(rom, v, out);
-                }
-            }
-            Value::Array(array) => {
-                for v in array {
-                    collect_warnings(rom, v, out);
-                }
-            }
-            _ => {}
-        }
-    }
-
     /// Ratchet: the set of resolve warnings (excluding "outside NVRAM") must
     /// match testdata/aaa_expected_warnings.txt exactly. A new unexpected
     /// warning fails here even when added together with a fresh golden
@@ -734,8 +1925,15 @@ mod tests {
                 .unwrap()
                 .to_string();
             let path = path_for_test(&test_dir, &nvram_path)?;
-            if let Some(value) = resolve(&path)? {
-                collect_warnings(&rom, &value, &mut actual);
+            for descriptor_warning in resolve_warnings(&path)? {
+                // Warnings for values outside the NVRAM are tolerated wholesale: the value
+                // is simply not part of the dumped NVRAM region, which is common and
+                // expected, so we do not enumerate them in the expected-warnings file.
+                if descriptor_warning.warning == ResolveWarning::OutsideNvram {
+                    continue;
+                }
+                let label = descriptor_warning.label.as_deref().unwrap_or("(no label)");
+                actual.push(format!("{rom} | {label} | {}", descriptor_warning.warning));
             }
         }
         let actual: std::collections::BTreeSet<String> = actual.into_iter().collect();
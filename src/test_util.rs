@@ -0,0 +1,57 @@
+//! Fixtures shared by the unit tests of the different modules.
+
+use crate::Nvram;
+use crate::model::{GlobalSettingsImpl, NvramMap, Platform};
+use serde_json::Value;
+use std::io;
+use std::path::Path;
+
+/// A big endian M6809 platform with the given memory layout
+pub(crate) fn platform(memory_layout: Value) -> Platform {
+    serde_json::from_value(serde_json::json!({
+        "cpu": "M6809",
+        "endian": "big",
+        "memory_layout": memory_layout
+    }))
+    .unwrap()
+}
+
+/// A platform with 16 bytes of NVRAM at address 0
+pub(crate) fn nvram_platform() -> Platform {
+    platform(serde_json::json!([
+        {"label": "NVRAM", "address": "0x0000", "size": "0x0010", "type": "nvram"}
+    ]))
+}
+
+/// Global settings for a map without shared values
+pub(crate) fn global_settings() -> GlobalSettingsImpl {
+    serde_json::from_value(serde_json::json!({
+        "_metadata": {"platform": "test", "version": 1, "roms": ["test"]}
+    }))
+    .unwrap()
+}
+
+/// A map without high scores, extended with the given sections
+pub(crate) fn map(sections: Value) -> io::Result<NvramMap> {
+    let mut map = serde_json::json!({
+        "_fileformat": 0.8,
+        "_metadata": {"platform": "test", "version": 1, "roms": ["test"]},
+        "high_scores": [],
+    });
+    map.as_object_mut()
+        .unwrap()
+        .extend(sections.as_object().unwrap().clone());
+    Ok(serde_json::from_value(map)?)
+}
+
+/// A NVRAM file `test.nv` in the directory with the `nvram` contents, on a platform with
+/// 6 bytes of NVRAM at address 0 and RAM at 0x100, using a [`map`] with the given sections
+pub(crate) fn nvram_with_map(dir: &Path, nvram: &[u8], sections: Value) -> io::Result<Nvram> {
+    let nv_path = dir.join("test.nv");
+    std::fs::write(&nv_path, nvram)?;
+    let platform = platform(serde_json::json!([
+        {"label": "NVRAM", "address": "0x0000", "size": "0x0006", "type": "nvram"},
+        {"label": "RAM", "address": "0x0100", "size": "0x0100", "type": "ram"}
+    ]));
+    Ok(Nvram::new(map(sections)?, platform, &nv_path))
+}
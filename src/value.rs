@@ -0,0 +1,431 @@
+use serde::{Serialize, Serializer};
+use serde_json::{Number, Value};
+use std::cmp::Ordering;
+use std::fmt;
+use std::io;
+
+/// A value decoded from the NVRAM
+#[derive(Debug, Clone, PartialEq)]
+pub enum NvValue {
+    /// A number, after `scale` has been applied
+    Integer(i128),
+    Text(String),
+    /// Text with bytes that are not a character, each shown as
+    /// [`char::REPLACEMENT_CHARACTER`]. `raw` has the byte of every character, so the text
+    /// can be written back unchanged.
+    UndecodedText {
+        text: String,
+        raw: Vec<u8>,
+    },
+    Bool(bool),
+    /// An entry selected from a list of `values` by its index.
+    /// The label is `None` if the index has no entry in the list.
+    Enum {
+        index: usize,
+        label: Option<Value>,
+    },
+    /// The state of each bit or switch, most significant first
+    Flags(Vec<bool>),
+    Bytes(Vec<u8>),
+    Timestamp(Timestamp),
+    /// The value is not stored in the NVRAM and there is no memory image to read it from
+    Unavailable,
+}
+
+impl fmt::Display for NvValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NvValue::Integer(value) => write!(f, "{value}"),
+            NvValue::Text(value) => f.write_str(value),
+            NvValue::UndecodedText { text, .. } => f.write_str(text),
+            NvValue::Timestamp(value) => write!(f, "{value}"),
+            NvValue::Bool(value) => write!(f, "{value}"),
+            NvValue::Enum {
+                label: Some(Value::String(label)),
+                ..
+            } => f.write_str(label),
+            NvValue::Enum {
+                label: Some(label), ..
+            } => write!(f, "{label}"),
+            NvValue::Enum { index, label: None } => write!(f, "{index}"),
+            NvValue::Flags(flags) => flags
+                .iter()
+                .try_for_each(|flag| f.write_str(if *flag { "1" } else { "0" })),
+            NvValue::Bytes(bytes) => {
+                let hex: Vec<String> = bytes.iter().map(|b| format!("{b:02X}")).collect();
+                f.write_str(&hex.join(" "))
+            }
+            NvValue::Unavailable => f.write_str("Value is stored outside the NVRAM"),
+        }
+    }
+}
+
+impl Serialize for NvValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            NvValue::Integer(value) => number_from_i128(*value).serialize(serializer),
+            NvValue::Text(value) => serializer.serialize_str(value),
+            NvValue::UndecodedText { text, .. } => serializer.serialize_str(text),
+            NvValue::Timestamp(value) => value.serialize(serializer),
+            NvValue::Bool(value) => serializer.serialize_bool(*value),
+            NvValue::Enum { label, .. } => label.serialize(serializer),
+            NvValue::Flags(flags) => flags.serialize(serializer),
+            NvValue::Bytes(bytes) => bytes.serialize(serializer),
+            NvValue::Unavailable => serializer.serialize_none(),
+        }
+    }
+}
+
+/// A date and time from a real-time clock, ordered chronologically
+///
+/// The fields are kept as stored, a clock that was never set reads as e.g. all `0xFF`,
+/// use [`Timestamp::is_valid`] to detect this.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Timestamp {
+    pub year: u16,
+    /// 1-12
+    pub month: u8,
+    /// 1-31
+    pub day: u8,
+    /// 1-7, 1 is Sunday
+    pub weekday: u8,
+    /// 0-23
+    pub hour: u8,
+    /// 0-59
+    pub minute: u8,
+}
+
+impl Ord for Timestamp {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // the weekday is stored between the day and the hour, it only breaks ties
+        (
+            self.year,
+            self.month,
+            self.day,
+            self.hour,
+            self.minute,
+            self.weekday,
+        )
+            .cmp(&(
+                other.year,
+                other.month,
+                other.day,
+                other.hour,
+                other.minute,
+                other.weekday,
+            ))
+    }
+}
+
+impl PartialOrd for Timestamp {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Timestamp {
+    /// A timestamp with the weekday calculated from the date, `None` if the date or
+    /// time does not exist.
+    pub fn new(year: u16, month: u8, day: u8, hour: u8, minute: u8) -> Option<Timestamp> {
+        let timestamp = Timestamp {
+            year,
+            month,
+            day,
+            weekday: weekday(year, month, day)?,
+            hour,
+            minute,
+        };
+        timestamp.is_valid().then_some(timestamp)
+    }
+
+    /// Whether all fields are in range and the day exists in the month.
+    ///
+    /// The weekday is not compared to the date, games do not always keep it in sync.
+    pub fn is_valid(&self) -> bool {
+        (1..=12).contains(&self.month)
+            && (1..=days_in_month(self.year, self.month)).contains(&self.day)
+            && (1..=7).contains(&self.weekday)
+            && self.hour < 24
+            && self.minute < 60
+    }
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        4 | 6 | 9 | 11 => 30,
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => {
+            29
+        }
+        2 => 28,
+        _ => 31,
+    }
+}
+
+/// The day of the week, 1 is Sunday, using Sakamoto's method
+fn weekday(year: u16, month: u8, day: u8) -> Option<u8> {
+    const MONTH_OFFSETS: [u32; 12] = [0, 3, 2, 5, 0, 3, 5, 1, 4, 6, 2, 4];
+    let offset = MONTH_OFFSETS.get(usize::from(month).checked_sub(1)?)?;
+    let year = u32::from(year).checked_sub(u32::from(month < 3))?;
+    let days = year + year / 4 - year / 100 + year / 400 + offset + u32::from(day);
+    Some((days % 7) as u8 + 1)
+}
+
+/// Formatted as `YYYY-MM-DD HH:MM`
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute
+        )
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+pub(crate) fn number_from_i128(v: i128) -> Number {
+    if (0..=u64::MAX as i128).contains(&v) {
+        Number::from(v as u64)
+    } else {
+        Number::from(v as i64)
+    }
+}
+
+/// Multiply a stored value by the `scale` of its descriptor.
+///
+/// The scale is used as an exact fraction, a `0.1` scale is `1/10`, so there is no
+/// rounding. Results that are not a whole number are an error.
+pub(crate) fn apply_scale(scale: &Number, value: u64) -> io::Result<i128> {
+    let (numerator, denominator) = scale_fraction(scale)?;
+    let scaled = i128::from(value)
+        .checked_mul(numerator)
+        .ok_or_else(|| scale_error(value, scale))?;
+    if scaled % denominator != 0 {
+        return Err(scale_error(value, scale));
+    }
+    Ok(scaled / denominator)
+}
+
+/// The stored value for a scaled value, the reverse of [`apply_scale`].
+///
+/// `None` if there is no stored value that scales to exactly this value.
+pub(crate) fn remove_scale(scale: &Number, value: i128) -> io::Result<Option<u64>> {
+    let (numerator, denominator) = scale_fraction(scale)?;
+    let stored = value
+        .checked_mul(denominator)
+        .filter(|stored| numerator != 0 && stored % numerator == 0)
+        .map(|stored| stored / numerator);
+    Ok(stored.and_then(|stored| u64::try_from(stored).ok()))
+}
+
+fn scale_error(value: u64, scale: &Number) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Value {value} can not be scaled by {scale} to an integer"),
+    )
+}
+
+/// A `scale` as numerator and positive denominator.
+///
+/// Floats are read from their shortest decimal representation, `0.1` is `1/10` and
+/// not the nearest binary fraction.
+fn scale_fraction(scale: &Number) -> io::Result<(i128, i128)> {
+    if let Some(integer) = scale.as_i64() {
+        return Ok((i128::from(integer), 1));
+    }
+    if let Some(integer) = scale.as_u64() {
+        return Ok((i128::from(integer), 1));
+    }
+    let invalid = || {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Unsupported scale {scale}"),
+        )
+    };
+    let text = scale.to_string();
+    let (mantissa, exponent) = match text.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse().map_err(|_| invalid())?),
+        None => (text.as_str(), 0i32),
+    };
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits: i128 = format!("{integer}{fraction}")
+        .parse()
+        .map_err(|_| invalid())?;
+    let exponent = exponent - fraction.len() as i32;
+    let power = 10i128
+        .checked_pow(exponent.unsigned_abs())
+        .ok_or_else(invalid)?;
+    if exponent >= 0 {
+        Ok((digits.checked_mul(power).ok_or_else(invalid)?, 1))
+    } else {
+        Ok((digits, power))
+    }
+}
+
+/// Add the `offset` of a descriptor to a scaled value
+pub(crate) fn apply_offset(value: i128, offset: i64) -> io::Result<i128> {
+    value.checked_add(i128::from(offset)).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Value {value} with offset {offset} overflows"),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_number_from_i128() {
+        assert_eq!(number_from_i128(255), Number::from(255u64));
+        assert_eq!(number_from_i128(0), Number::from(0u64));
+        assert_eq!(number_from_i128(-3), Number::from(-3i64));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(NvValue::Integer(-3).to_string(), "-3");
+        assert_eq!(NvValue::Bool(true).to_string(), "true");
+        assert_eq!(
+            NvValue::Enum {
+                index: 1,
+                label: Some(Value::from("Auto %"))
+            }
+            .to_string(),
+            "Auto %"
+        );
+        assert_eq!(
+            NvValue::Enum {
+                index: 1,
+                label: Some(Value::from(5))
+            }
+            .to_string(),
+            "5"
+        );
+        assert_eq!(NvValue::Flags(vec![true, false, true]).to_string(), "101");
+        assert_eq!(NvValue::Bytes(vec![0x01, 0xAB]).to_string(), "01 AB");
+        let timestamp = Timestamp::new(2024, 6, 9, 0, 1).unwrap();
+        assert_eq!(
+            NvValue::Timestamp(timestamp).to_string(),
+            "2024-06-09 00:01"
+        );
+    }
+
+    #[test]
+    fn test_serialize() -> serde_json::Result<()> {
+        assert_eq!(
+            serde_json::to_value(NvValue::Flags(vec![true, false]))?,
+            serde_json::json!([true, false])
+        );
+        assert_eq!(
+            serde_json::to_value(NvValue::Unavailable)?,
+            serde_json::json!(null)
+        );
+        let timestamp = Timestamp::new(2023, 11, 7, 0, 14).unwrap();
+        assert_eq!(
+            serde_json::to_value(NvValue::Timestamp(timestamp))?,
+            serde_json::json!("2023-11-07 00:14")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_timestamp_order() {
+        let earlier = Timestamp::new(2023, 12, 31, 23, 59).unwrap();
+        let later = Timestamp::new(2024, 1, 1, 0, 0).unwrap();
+        assert!(earlier < later);
+        // the morning is earlier, even with a wrong (later) weekday stored
+        let morning = Timestamp {
+            weekday: 2,
+            ..Timestamp::new(2024, 6, 16, 8, 0).unwrap()
+        };
+        let evening = Timestamp::new(2024, 6, 16, 20, 0).unwrap();
+        assert_eq!(1, evening.weekday);
+        assert!(morning < evening);
+    }
+
+    #[test]
+    fn test_timestamp_weekday() {
+        // a Sunday, Monday and Saturday
+        assert_eq!(
+            Some(1),
+            Timestamp::new(2024, 6, 16, 0, 0).map(|t| t.weekday)
+        );
+        assert_eq!(
+            Some(2),
+            Timestamp::new(2023, 12, 11, 0, 0).map(|t| t.weekday)
+        );
+        assert_eq!(Some(7), Timestamp::new(2000, 1, 1, 0, 0).map(|t| t.weekday));
+        assert_eq!(
+            Some(5),
+            Timestamp::new(2024, 2, 29, 0, 0).map(|t| t.weekday)
+        );
+    }
+
+    #[test]
+    fn test_timestamp_is_valid() {
+        assert_eq!(None, Timestamp::new(2023, 2, 29, 0, 0));
+        assert_eq!(None, Timestamp::new(2024, 13, 1, 0, 0));
+        assert_eq!(None, Timestamp::new(2024, 1, 1, 24, 0));
+        assert_eq!(None, Timestamp::new(2024, 1, 1, 0, 60));
+        let unset = Timestamp {
+            year: 0xFFFF,
+            month: 0xFF,
+            day: 0xFF,
+            weekday: 0xFF,
+            hour: 0xFF,
+            minute: 0xFF,
+        };
+        assert!(!unset.is_valid());
+        let weekday_out_of_sync = Timestamp {
+            weekday: 3,
+            ..Timestamp::new(2024, 6, 16, 0, 0).unwrap()
+        };
+        assert!(weekday_out_of_sync.is_valid());
+    }
+
+    #[test]
+    fn test_apply_scale() -> io::Result<()> {
+        let scale = |s: &str| -> Number { serde_json::from_str(s).unwrap() };
+        assert_eq!(apply_scale(&scale("1"), u64::MAX)?, u64::MAX as i128);
+        assert_eq!(
+            apply_scale(&scale("1000"), 99_999_999_999_999)?,
+            99_999_999_999_999_000
+        );
+        assert_eq!(apply_scale(&scale("-2"), 21)?, -42);
+        // 29 * 0.01 is 0.29 and 0.29 * 100 is 28.999999999999996 with f64
+        assert_eq!(apply_scale(&scale("0.01"), 2900)?, 29);
+        assert_eq!(apply_scale(&scale("0.5"), 6)?, 3);
+        assert_eq!(apply_scale(&scale("1e3"), 7)?, 7000);
+        assert_eq!(
+            apply_scale(&scale("0.5"), 7).unwrap_err().to_string(),
+            "Value 7 can not be scaled by 0.5 to an integer"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_remove_scale() -> io::Result<()> {
+        let scale = |s: &str| -> Number { serde_json::from_str(s).unwrap() };
+        assert_eq!(remove_scale(&scale("10"), 1230)?, Some(123));
+        assert_eq!(remove_scale(&scale("10"), 1234)?, None);
+        assert_eq!(remove_scale(&scale("0.5"), 3)?, Some(6));
+        assert_eq!(remove_scale(&scale("-2"), -42)?, Some(21));
+        assert_eq!(remove_scale(&scale("1"), -1)?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_offset() -> io::Result<()> {
+        assert_eq!(apply_offset(0, -1)?, -1);
+        assert_eq!(apply_offset(5, 1)?, 6);
+        assert!(apply_offset(i128::MAX, 1).is_err());
+        Ok(())
+    }
+}
@@ -108,5 +108,11 @@ fn test_attack_from_mars() -> io::Result<()> {
     ];
     assert_eq!(expected, scores);
 
+    let last_played = nvram.read_last_played()?;
+    assert_eq!(
+        Some("2024-06-19 00:01".to_string()),
+        last_played.map(|timestamp| timestamp.to_string())
+    );
+
     Ok(())
 }
@@ -1,4 +1,5 @@
 use pinmame_nvram::checksum::ChecksumMismatch;
+use pinmame_nvram::value::NvValue;
 use pinmame_nvram::{HighScore, LastGamePlayer, ModeChampion, Nvram};
 use pretty_assertions::assert_eq;
 use std::io;
@@ -79,6 +80,27 @@ fn test_demolition_man() -> io::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_demolition_man_get_set() -> io::Result<()> {
+    let dir = testdir!();
+    let test_file = dir.join("dm_lx4.nv");
+    std::fs::copy("testdata/dm_lx4.nv", &test_file)?;
+    let mut nvram = Nvram::open(&test_file)?.unwrap();
+    assert_eq!(NvValue::Integer(9), nvram.get("game_state/credits")?);
+    nvram.set("game_state/credits", NvValue::Integer(12))?;
+    assert_eq!(NvValue::Integer(12), nvram.get("game_state/credits")?);
+
+    let balls_per_game = "adjustments/A.1 Standard Adjustments/Balls Per Game";
+    assert_eq!(NvValue::Integer(3), nvram.get(balls_per_game)?);
+    nvram.set(balls_per_game, NvValue::Integer(5))?;
+    assert_eq!(NvValue::Integer(5), nvram.get(balls_per_game)?);
+    assert_eq!(
+        Vec::<ChecksumMismatch<u16>>::new(),
+        nvram.verify_all_checksum16()?
+    );
+    Ok(())
+}
+
 #[test]
 fn test_demolition_man_clear_scores() -> io::Result<()> {
     let dir = testdir!();
@@ -1,4 +1,4 @@
-use pinmame_nvram::{HighScore, LastGamePlayer, Nvram};
+use pinmame_nvram::{GameState, HighScore, LastGamePlayer, Nvram};
 use pretty_assertions::assert_eq;
 use std::collections::HashMap;
 use std::io;
@@ -33,6 +33,24 @@ fn test_earthshaker_la3() -> io::Result<()> {
     ]);
     assert_eq!(Some(expected), game_state);
 
+    let game_state = nvram.read_typed_game_state()?;
+    let expected = GameState {
+        credits: Some(5),
+        max_credits: Some(10),
+        current_player: Some(1),
+        current_ball: Some(3),
+        ball_count: Some(3),
+        player_count: Some(1),
+        tilt_warnings: Some(0),
+        tilted: Some(false),
+        game_over: Some(false),
+        extra_balls: Some(0),
+        free_play: Some(false),
+        scores: vec![Some(869_880), Some(0), Some(0), Some(0)],
+        extras: HashMap::new(),
+    };
+    assert_eq!(Some(expected), game_state);
+
     let last_game = nvram.read_last_game()?;
     let expected = vec![
         LastGamePlayer {
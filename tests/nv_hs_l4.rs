@@ -70,8 +70,14 @@ fn test_high_speed() -> io::Result<()> {
         ("extra_balls".into(), "3".into()),
         ("tilt_warnings".into(), "0".into()),
         ("bonus_hold".into(), "0".into()),
-        ("bonus".into(), "Bits encoding not implemented".into()),
-        ("bonusX".into(), "Bits encoding not implemented".into()),
+        (
+            "bonus".into(),
+            "Reading bits values is not supported".into(),
+        ),
+        (
+            "bonusX".into(),
+            "Reading bits values is not supported".into(),
+        ),
         ("current_player".into(), "0".into()),
         ("player_count".into(), "0".into()),
         ("match".into(), "0".into()),